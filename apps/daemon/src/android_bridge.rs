@@ -1,483 +1,958 @@
-use crate::config::DaemonConfig;
-use crate::backup::BackupManager;
-use crate::filesystem::FileSystemMonitor;
-use crate::kernel_interface::KernelInterface;
-
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{RwLock, mpsc};
-use tokio_tungstenite::{accept_async, WebSocketStream};
-use tokio_tungstenite::tungstenite::Message;
-use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
-use std::sync::Arc;
-use std::collections::HashMap;
-use tracing::{info, error, warn, debug};
-use uuid::Uuid;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AndroidMessage {
-    pub id: String,
-    pub message_type: AndroidMessageType,
-    pub payload: serde_json::Value,
-    pub timestamp: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
-pub enum AndroidMessageType {
-    // Authentication
-    Auth { token: String },
-    AuthResponse { success: bool, device_id: String },
-    
-    // Device Management
-    RegisterDevice { device_info: DeviceInfo },
-    DeviceStatus { status: DeviceStatus },
-    
-    // Backup Operations
-    StartBackup { paths: Vec<String>, options: BackupOptions },
-    PauseBackup { job_id: String },
-    ResumeBackup { job_id: String },
-    CancelBackup { job_id: String },
-    BackupProgress { job_id: String, progress: f32, details: String },
-    BackupComplete { job_id: String, success: bool, details: String },
-    
-    // File Operations
-    ListFiles { path: String },
-    FileList { files: Vec<FileInfo> },
-    RestoreFile { file_path: String, restore_path: String },
-    RestoreProgress { progress: f32, details: String },
-    
-    // System Status
-    GetSystemStatus,
-    SystemStatus { status: SystemStatusInfo },
-    GetLogs { level: String, lines: u32 },
-    LogData { logs: Vec<String> },
-    
-    // Configuration
-    GetConfig,
-    UpdateConfig { config: serde_json::Value },
-    ConfigResponse { success: bool, message: String },
-    
-    // Real-time notifications
-    FileChanged { path: String, change_type: String },
-    SystemAlert { level: String, message: String },
-    
-    // Kernel Module
-    GetKernelStatus,
-    KernelStatus { loaded: bool, version: String, features: Vec<String> },
-    
-    // Error handling
-    Error { code: u32, message: String },
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DeviceInfo {
-    pub device_id: String,
-    pub device_name: String,
-    pub os_version: String,
-    pub app_version: String,
-    pub hardware_info: HashMap<String, String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DeviceStatus {
-    pub online: bool,
-    pub last_backup: Option<u64>,
-    pub storage_usage: StorageInfo,
-    pub network_status: NetworkStatus,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StorageInfo {
-    pub total_space: u64,
-    pub free_space: u64,
-    pub backup_usage: u64,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NetworkStatus {
-    pub connected: bool,
-    pub connection_type: String,
-    pub signal_strength: i32,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BackupOptions {
-    pub incremental: bool,
-    pub compression: bool,
-    pub encryption: bool,
-    pub priority: u8,
-    pub exclude_patterns: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct FileInfo {
-    pub path: String,
-    pub size: u64,
-    pub modified: u64,
-    pub file_type: String,
-    pub backed_up: bool,
-    pub backup_time: Option<u64>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SystemStatusInfo {
-    pub daemon_uptime: u64,
-    pub active_backups: u32,
-    pub total_files_backed_up: u64,
-    pub total_backup_size: u64,
-    pub memory_usage: u64,
-    pub cpu_usage: f32,
-    pub kernel_module_loaded: bool,
-    pub services_status: HashMap<String, bool>,
-}
-
-pub struct AndroidClient {
-    pub device_id: String,
-    pub device_info: Option<DeviceInfo>,
-    pub websocket: WebSocketStream<TcpStream>,
-    pub message_sender: mpsc::UnboundedSender<AndroidMessage>,
-    pub authenticated: bool,
-    pub last_heartbeat: std::time::Instant,
-}
-
-pub struct AndroidBridge {
-    config: Arc<DaemonConfig>,
-    backup_manager: Arc<RwLock<BackupManager>>,
-    fs_monitor: Arc<RwLock<FileSystemMonitor>>,
-    kernel_interface: Arc<KernelInterface>,
-    clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
-    event_sender: mpsc::UnboundedSender<AndroidMessage>,
-}
-
-impl AndroidBridge {
-    pub async fn new(
-        config: &Arc<DaemonConfig>,
-        backup_manager: Arc<RwLock<BackupManager>>,
-        fs_monitor: Arc<RwLock<FileSystemMonitor>>,
-        kernel_interface: Arc<KernelInterface>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let (event_sender, _) = mpsc::unbounded_channel();
-        
-        Ok(Self {
-            config: config.clone(),
-            backup_manager,
-            fs_monitor,
-            kernel_interface,
-            clients: Arc::new(RwLock::new(HashMap::new())),
-            event_sender,
-        })
-    }
-
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let addr = format!("{}:{}", "0.0.0.0", self.config.android.bridge_port);
-        let listener = TcpListener::bind(&addr).await?;
-        info!("Android bridge listening on {}", addr);
-
-        // Start heartbeat checker
-        let clients = self.clients.clone();
-        let heartbeat_interval = self.config.android.heartbeat_interval;
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(
-                std::time::Duration::from_secs(heartbeat_interval)
-            );
-            
-            loop {
-                interval.tick().await;
-                Self::check_client_heartbeats(clients.clone(), heartbeat_interval * 2).await;
-            }
-        });
-
-        while let Ok((stream, addr)) = listener.accept().await {
-            info!("New Android connection from {}", addr);
-            
-            let clients = self.clients.clone();
-            let config = self.config.clone();
-            let backup_manager = self.backup_manager.clone();
-            let fs_monitor = self.fs_monitor.clone();
-            let kernel_interface = self.kernel_interface.clone();
-            
-            tokio::spawn(async move {
-                if let Err(e) = Self::handle_client(
-                    stream, clients, config, backup_manager, fs_monitor, kernel_interface
-                ).await {
-                    error!("Client handler error: {}", e);
-                }
-            });
-        }
-
-        Ok(())
-    }
-
-    async fn handle_client(
-        stream: TcpStream,
-        clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
-        config: Arc<DaemonConfig>,
-        backup_manager: Arc<RwLock<BackupManager>>,
-        fs_monitor: Arc<RwLock<FileSystemMonitor>>,
-        kernel_interface: Arc<KernelInterface>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let websocket = accept_async(stream).await?;
-        let (mut ws_sender, mut ws_receiver) = websocket.split();
-        
-        let (msg_sender, mut msg_receiver) = mpsc::unbounded_channel();
-        let client_id = Uuid::new_v4().to_string();
-        
-        // Handle outgoing messages
-        let sender_handle = tokio::spawn(async move {
-            while let Some(message) = msg_receiver.recv().await {
-                let json = serde_json::to_string(&message).unwrap();
-                if let Err(e) = ws_sender.send(Message::Text(json)).await {
-                    error!("Failed to send message to client: {}", e);
-                    break;
-                }
-            }
-        });
-
-        // Handle incoming messages
-        while let Some(msg) = ws_receiver.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if let Ok(android_msg) = serde_json::from_str::<AndroidMessage>(&text) {
-                        Self::process_message(
-                            android_msg,
-                            &client_id,
-                            clients.clone(),
-                            config.clone(),
-                            backup_manager.clone(),
-                            fs_monitor.clone(),
-                            kernel_interface.clone(),
-                            msg_sender.clone(),
-                        ).await;
-                    } else {
-                        error!("Failed to parse Android message: {}", text);
-                    }
-                }
-                Ok(Message::Close(_)) => {
-                    info!("Client {} disconnected", client_id);
-                    break;
-                }
-                Err(e) => {
-                    error!("WebSocket error: {}", e);
-                    break;
-                }
-                _ => {}
-            }
-        }
-
-        // Cleanup
-        clients.write().await.remove(&client_id);
-        sender_handle.abort();
-        
-        Ok(())
-    }
-
-    async fn process_message(
-        message: AndroidMessage,
-        client_id: &str,
-        clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
-        config: Arc<DaemonConfig>,
-        backup_manager: Arc<RwLock<BackupManager>>,
-        fs_monitor: Arc<RwLock<FileSystemMonitor>>,
-        kernel_interface: Arc<KernelInterface>,
-        sender: mpsc::UnboundedSender<AndroidMessage>,
-    ) {
-        debug!("Processing message: {:?}", message.message_type);
-
-        match message.message_type {
-            AndroidMessageType::Auth { token } => {
-                let success = token == config.android.auth_token;
-                let device_id = if success {
-                    Uuid::new_v4().to_string()
-                } else {
-                    "unauthorized".to_string()
-                };
-
-                let response = AndroidMessage {
-                    id: Uuid::new_v4().to_string(),
-                    message_type: AndroidMessageType::AuthResponse { success, device_id: device_id.clone() },
-                    payload: serde_json::Value::Null,
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                };
-
-                if success {
-                    info!("Client {} authenticated as device {}", client_id, device_id);
-                }
-
-                let _ = sender.send(response);
-            }
-
-            AndroidMessageType::GetSystemStatus => {
-                let backup_manager = backup_manager.read().await;
-                let status = SystemStatusInfo {
-                    daemon_uptime: 12345, // TODO: Calculate actual uptime
-                    active_backups: backup_manager.get_active_job_count().await,
-                    total_files_backed_up: backup_manager.get_total_files_backed_up().await,
-                    total_backup_size: backup_manager.get_total_backup_size().await,
-                    memory_usage: Self::get_memory_usage(),
-                    cpu_usage: Self::get_cpu_usage(),
-                    kernel_module_loaded: kernel_interface.is_loaded().await,
-                    services_status: Self::get_services_status().await,
-                };
-
-                let response = AndroidMessage {
-                    id: Uuid::new_v4().to_string(),
-                    message_type: AndroidMessageType::SystemStatus { status },
-                    payload: serde_json::Value::Null,
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                };
-
-                let _ = sender.send(response);
-            }
-
-            AndroidMessageType::StartBackup { paths, options } => {
-                let backup_manager = backup_manager.write().await;
-                match backup_manager.start_backup(paths, options).await {
-                    Ok(job_id) => {
-                        info!("Started backup job: {}", job_id);
-                        // Send progress updates will be handled by backup manager
-                    }
-                    Err(e) => {
-                        error!("Failed to start backup: {}", e);
-                        let error_response = AndroidMessage {
-                            id: Uuid::new_v4().to_string(),
-                            message_type: AndroidMessageType::Error { 
-                                code: 1001, 
-                                message: format!("Failed to start backup: {}", e) 
-                            },
-                            payload: serde_json::Value::Null,
-                            timestamp: std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs(),
-                        };
-                        let _ = sender.send(error_response);
-                    }
-                }
-            }
-
-            AndroidMessageType::ListFiles { path } => {
-                match fs_monitor.read().await.list_files(&path).await {
-                    Ok(files) => {
-                        let response = AndroidMessage {
-                            id: Uuid::new_v4().to_string(),
-                            message_type: AndroidMessageType::FileList { files },
-                            payload: serde_json::Value::Null,
-                            timestamp: std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs(),
-                        };
-                        let _ = sender.send(response);
-                    }
-                    Err(e) => {
-                        error!("Failed to list files: {}", e);
-                        let error_response = AndroidMessage {
-                            id: Uuid::new_v4().to_string(),
-                            message_type: AndroidMessageType::Error { 
-                                code: 1002, 
-                                message: format!("Failed to list files: {}", e) 
-                            },
-                            payload: serde_json::Value::Null,
-                            timestamp: std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap()
-                                .as_secs(),
-                        };
-                        let _ = sender.send(error_response);
-                    }
-                }
-            }
-
-            AndroidMessageType::GetKernelStatus => {
-                let status = kernel_interface.get_status().await;
-                let response = AndroidMessage {
-                    id: Uuid::new_v4().to_string(),
-                    message_type: AndroidMessageType::KernelStatus { 
-                        loaded: status.loaded,
-                        version: status.version,
-                        features: status.features 
-                    },
-                    payload: serde_json::Value::Null,
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
-                };
-                let _ = sender.send(response);
-            }
-
-            _ => {
-                warn!("Unhandled message type: {:?}", message.message_type);
-            }
-        }
-    }
-
-    async fn check_client_heartbeats(
-        clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
-        timeout_seconds: u64,
-    ) {
-        let mut clients_to_remove = Vec::new();
-        let timeout_duration = std::time::Duration::from_secs(timeout_seconds);
-        
-        {
-            let clients_read = clients.read().await;
-            for (client_id, client) in clients_read.iter() {
-                if client.last_heartbeat.elapsed() > timeout_duration {
-                    clients_to_remove.push(client_id.clone());
-                }
-            }
-        }
-
-        if !clients_to_remove.is_empty() {
-            let mut clients_write = clients.write().await;
-            for client_id in clients_to_remove {
-                warn!("Removing inactive client: {}", client_id);
-                clients_write.remove(&client_id);
-            }
-        }
-    }
-
-    fn get_memory_usage() -> u64 {
-        // TODO: Implement actual memory usage calculation
-        64 * 1024 * 1024 // 64MB placeholder
-    }
-
-    fn get_cpu_usage() -> f32 {
-        // TODO: Implement actual CPU usage calculation
-        15.5 // 15.5% placeholder
-    }
-
-    async fn get_services_status() -> HashMap<String, bool> {
-        // TODO: Implement actual service health checks
-        let mut status = HashMap::new();
-        status.insert("backup_engine".to_string(), true);
-        status.insert("storage_hal".to_string(), true);
-        status.insert("compression_engine".to_string(), true);
-        status.insert("encryption_service".to_string(), false);
-        status.insert("ml_optimizer".to_string(), true);
-        status
-    }
-
-    pub async fn broadcast_message(&self, message: AndroidMessage) {
-        let clients = self.clients.read().await;
-        for (_, client) in clients.iter() {
-            let _ = client.message_sender.send(message.clone());
-        }
-    }
-
-    pub async fn send_to_device(&self, device_id: &str, message: AndroidMessage) -> bool {
-        let clients = self.clients.read().await;
-        if let Some(client) = clients.get(device_id) {
-            client.message_sender.send(message).is_ok()
-        } else {
-            false
-        }
-    }
+use crate::config::DaemonConfig;
+use crate::backup::{BackupManager, RestoreSelector, VersionKind};
+use crate::background_runner::{BackgroundRunner, JobPriority};
+use crate::cache::{build_cache_adapter, CacheAdapter};
+use crate::filesystem::FileSystemMonitor;
+use crate::kernel_interface::KernelInterface;
+use crate::pairing::PairingManager;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{RwLock, mpsc};
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tokio_tungstenite::{accept_async, WebSocketStream};
+use tokio_tungstenite::tungstenite::Message;
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::collections::HashMap;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tracing::{info, error, warn, debug};
+use uuid::Uuid;
+
+/// How long `handle_client` waits for a client's sender task to deliver its
+/// final Close frame and exit during a bridge-wide shutdown before giving up
+/// on it, so one wedged socket write can't hang the whole bridge's shutdown.
+const SENDER_SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Either a plain TCP connection or one already wrapped in TLS by a
+/// `TlsAcceptor` - lets `handle_client` stay agnostic to which transport
+/// mode the bridge is running in.
+enum ClientStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl AsyncRead for ClientStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            ClientStream::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ClientStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            ClientStream::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            ClientStream::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(s) => Pin::new(s).poll_flush(cx),
+            ClientStream::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            ClientStream::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
+
+/// Loads a `TlsAcceptor` from the cert/key paths in `config.android`, for
+/// wrapping accepted sockets before the WebSocket handshake runs over them.
+fn build_tls_acceptor(config: &DaemonConfig) -> Result<TlsAcceptor, Box<dyn std::error::Error>> {
+    let cert_path = config.android.cert_path.as_ref().ok_or("TLS enabled but cert_path is not set")?;
+    let key_path = config.android.key_path.as_ref().ok_or("TLS enabled but key_path is not set")?;
+
+    let cert_bytes = std::fs::read(cert_path)?;
+    let key_bytes = std::fs::read(key_path)?;
+
+    let certs = rustls_pemfile::certs(&mut cert_bytes.as_slice()).collect::<Result<Vec<_>, _>>()?;
+    let key = rustls_pemfile::private_key(&mut key_bytes.as_slice())?
+        .ok_or("No private key found in key_path")?;
+
+    let tls_config = tokio_rustls::rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+
+    Ok(TlsAcceptor::from(Arc::new(tls_config)))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AndroidMessage {
+    pub id: String,
+    /// For a response, the `id` of the request it answers - lets a client
+    /// issuing concurrent calls (e.g. `ListFiles` and `StartBackup` at once)
+    /// match replies back to the request that triggered them. `None` on
+    /// messages that don't answer a specific request (e.g. broadcasts).
+    #[serde(default)]
+    pub correlation_id: Option<String>,
+    pub message_type: AndroidMessageType,
+    pub payload: serde_json::Value,
+    pub timestamp: u64,
+}
+
+impl AndroidMessage {
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    }
+
+    /// Builds a response message that echoes `request_id` as its correlation id.
+    fn reply_to(request_id: &str, message_type: AndroidMessageType) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            correlation_id: Some(request_id.to_string()),
+            message_type,
+            payload: serde_json::Value::Null,
+            timestamp: Self::now(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AndroidMessageType {
+    // Authentication
+    /// `token` is either a freshly-scanned pairing secret (first connection)
+    /// or a previously-issued per-device token (every connection after).
+    Auth { token: String, device_id: String },
+    AuthResponse { success: bool, device_id: String, device_token: Option<String> },
+    /// Mints a short-lived pairing code, gated on the daemon's admin token
+    /// rather than any per-device credential.
+    BeginPairing { admin_token: String },
+    PairingCode { qr_png_base64: String, expires_at: u64 },
+    /// Revokes a previously paired device's credential, gated on the
+    /// daemon's admin token like `BeginPairing` - the device must be
+    /// re-paired through a fresh QR code to regain access.
+    RevokeDevice { admin_token: String, device_id: String },
+    DeviceRevoked { device_id: String },
+
+    // Device Management
+    RegisterDevice { device_info: DeviceInfo },
+    DeviceStatus { status: DeviceStatus },
+    
+    // Backup Operations
+    StartBackup { paths: Vec<String>, options: BackupOptions },
+    PauseBackup { job_id: String },
+    ResumeBackup { job_id: String },
+    CancelBackup { job_id: String },
+    BackupProgress { job_id: String, progress: f32, details: String },
+    BackupComplete { job_id: String, success: bool, details: String },
+    CompactNow,
+    GetBackupHistory { path: String },
+    BackupHistory { versions: Vec<BackupVersionInfo> },
+
+    // File Operations
+    ListFiles { path: String },
+    FileList { files: Vec<FileInfo> },
+    RestoreFile { file_path: String, restore_path: String, version: Option<usize>, as_of_timestamp: Option<u64> },
+    RestoreProgress { progress: f32, details: String },
+    ListVersions { path: String },
+    VersionList { versions: Vec<BackupVersionInfo> },
+    
+    // System Status
+    GetSystemStatus,
+    SystemStatus { status: SystemStatusInfo },
+    GetLogs { level: String, lines: u32 },
+    LogData { logs: Vec<String> },
+    
+    // Configuration
+    GetConfig,
+    UpdateConfig { config: serde_json::Value },
+    ConfigResponse { success: bool, message: String },
+    
+    // Real-time notifications
+    FileChanged { path: String, change_type: String },
+    SystemAlert { level: String, message: String },
+    
+    // Kernel Module
+    GetKernelStatus,
+    KernelStatus { loaded: bool, version: String, features: Vec<String> },
+    
+    // Error handling
+    Error { code: u32, message: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    pub device_id: String,
+    pub device_name: String,
+    pub os_version: String,
+    pub app_version: String,
+    pub hardware_info: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceStatus {
+    pub online: bool,
+    pub last_backup: Option<u64>,
+    pub storage_usage: StorageInfo,
+    pub network_status: NetworkStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageInfo {
+    pub total_space: u64,
+    pub free_space: u64,
+    pub backup_usage: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkStatus {
+    pub connected: bool,
+    pub connection_type: String,
+    pub signal_strength: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupOptions {
+    pub incremental: bool,
+    pub compression: bool,
+    pub encryption: bool,
+    pub priority: u8,
+    pub exclude_patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVersionInfo {
+    pub path: String,
+    pub size: u64,
+    pub block_count: usize,
+    pub created_at: u64,
+    pub is_delete_marker: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileInfo {
+    pub path: String,
+    pub size: u64,
+    pub modified: u64,
+    pub file_type: String,
+    pub backed_up: bool,
+    pub backup_time: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemStatusInfo {
+    pub daemon_uptime: u64,
+    pub active_backups: u32,
+    pub total_files_backed_up: u64,
+    pub total_backup_size: u64,
+    pub memory_usage: u64,
+    pub cpu_usage: f32,
+    pub kernel_module_loaded: bool,
+    pub services_status: HashMap<String, bool>,
+}
+
+pub struct AndroidClient {
+    pub device_id: String,
+    pub device_info: Option<DeviceInfo>,
+    pub websocket: WebSocketStream<ClientStream>,
+    pub message_sender: mpsc::UnboundedSender<AndroidMessage>,
+    pub authenticated: bool,
+    pub last_heartbeat: std::time::Instant,
+}
+
+/// Tracks a request that can't be answered immediately - currently just
+/// `StartBackup` - so the background job that eventually finishes it can
+/// resolve or reject back to the right client with the right correlation id.
+struct PendingRequest {
+    request_id: String,
+    sender: mpsc::UnboundedSender<AndroidMessage>,
+}
+
+pub struct AndroidBridge {
+    config: Arc<DaemonConfig>,
+    backup_manager: Arc<RwLock<BackupManager>>,
+    fs_monitor: Arc<RwLock<FileSystemMonitor>>,
+    kernel_interface: Arc<KernelInterface>,
+    runner: Arc<BackgroundRunner>,
+    pairing: Arc<PairingManager>,
+    cache: Arc<dyn CacheAdapter>,
+    clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
+    pending_requests: Arc<RwLock<HashMap<String, PendingRequest>>>,
+    event_sender: mpsc::UnboundedSender<AndroidMessage>,
+    shutdown_notify: Arc<tokio::sync::Notify>,
+}
+
+impl AndroidBridge {
+    pub async fn new(
+        config: &Arc<DaemonConfig>,
+        backup_manager: Arc<RwLock<BackupManager>>,
+        fs_monitor: Arc<RwLock<FileSystemMonitor>>,
+        kernel_interface: Arc<KernelInterface>,
+        runner: Arc<BackgroundRunner>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (event_sender, _) = mpsc::unbounded_channel();
+        let cache = build_cache_adapter(&config.cache)?;
+
+        Ok(Self {
+            config: config.clone(),
+            backup_manager,
+            fs_monitor,
+            kernel_interface,
+            runner,
+            pairing: Arc::new(PairingManager::new()),
+            cache,
+            clients: Arc::new(RwLock::new(HashMap::new())),
+            pending_requests: Arc::new(RwLock::new(HashMap::new())),
+            event_sender,
+            shutdown_notify: Arc::new(tokio::sync::Notify::new()),
+        })
+    }
+
+    /// Accepts connections until `shutdown_signal` resolves. On shutdown, every
+    /// connected client's sender loop is woken to send a `Close` frame, the
+    /// heartbeat checker is aborted, and in-flight client tasks are awaited
+    /// before returning - so the bridge can be embedded under systemd/SIGTERM
+    /// without dropping connections abruptly.
+    pub async fn start(&self, shutdown_signal: impl std::future::Future<Output = ()>) -> Result<(), Box<dyn std::error::Error>> {
+        let addr = format!("{}:{}", "0.0.0.0", self.config.android.bridge_port);
+        let listener = TcpListener::bind(&addr).await?;
+
+        let tls_acceptor = if self.config.android.tls_enabled {
+            Some(build_tls_acceptor(&self.config)?)
+        } else {
+            None
+        };
+        info!("Android bridge listening on {} ({})", addr, if tls_acceptor.is_some() { "wss" } else { "ws" });
+
+        // Start heartbeat checker. This runs for the daemon's whole lifetime,
+        // so it's spawned directly rather than through the bounded
+        // `BackgroundRunner` pool - going through `submit` would permanently
+        // pin one of its slots and silently shrink real client capacity by
+        // one for as long as the bridge is up.
+        let clients = self.clients.clone();
+        let heartbeat_interval = self.config.android.heartbeat_interval;
+        let heartbeat_handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(
+                std::time::Duration::from_secs(heartbeat_interval)
+            );
+
+            loop {
+                interval.tick().await;
+                Self::check_client_heartbeats(clients.clone(), heartbeat_interval * 2).await;
+            }
+        });
+        let heartbeat_abort = heartbeat_handle.abort_handle();
+
+        tokio::pin!(shutdown_signal);
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    let (stream, addr) = match accepted {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            error!("Failed to accept Android connection: {}", e);
+                            break;
+                        }
+                    };
+                    info!("New Android connection from {}", addr);
+
+                    let clients = self.clients.clone();
+                    let config = self.config.clone();
+                    let backup_manager = self.backup_manager.clone();
+                    let fs_monitor = self.fs_monitor.clone();
+                    let kernel_interface = self.kernel_interface.clone();
+                    let runner = self.runner.clone();
+                    let pairing = self.pairing.clone();
+                    let cache = self.cache.clone();
+                    let pending_requests = self.pending_requests.clone();
+                    let shutdown_notify = self.shutdown_notify.clone();
+                    let tls_acceptor = tls_acceptor.clone();
+
+                    // `submit` waits for a free worker-pool slot, and a
+                    // matched `select!` arm runs to completion before the
+                    // loop is polled again - so waiting for that slot here
+                    // would also block new connections from being accepted
+                    // and block `shutdown_signal` from ever being observed.
+                    // Dispatching through an un-gated spawn keeps the pool
+                    // wait off this loop; only the per-client work itself is
+                    // bounded by it.
+                    tokio::spawn(async move {
+                        runner.submit(format!("client-{}", addr), JobPriority::Normal, async move {
+                            let stream = match tls_acceptor {
+                                Some(acceptor) => match acceptor.accept(stream).await {
+                                    Ok(tls_stream) => ClientStream::Tls(Box::new(tls_stream)),
+                                    Err(e) => {
+                                        error!("TLS handshake with {} failed: {}", addr, e);
+                                        return;
+                                    }
+                                },
+                                None => ClientStream::Plain(stream),
+                            };
+
+                            if let Err(e) = Self::handle_client(
+                                stream, clients, config, backup_manager, fs_monitor, kernel_interface, pairing, cache, pending_requests, shutdown_notify
+                            ).await {
+                                error!("Client handler error: {}", e);
+                            }
+                        }).await;
+                    });
+                }
+                _ = &mut shutdown_signal => {
+                    info!("Android bridge received shutdown signal");
+                    break;
+                }
+            }
+        }
+
+        heartbeat_abort.abort();
+        self.shutdown_notify.notify_waiters();
+        self.runner.shutdown().await;
+
+        Ok(())
+    }
+
+    async fn handle_client(
+        stream: ClientStream,
+        clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
+        config: Arc<DaemonConfig>,
+        backup_manager: Arc<RwLock<BackupManager>>,
+        fs_monitor: Arc<RwLock<FileSystemMonitor>>,
+        kernel_interface: Arc<KernelInterface>,
+        pairing: Arc<PairingManager>,
+        cache: Arc<dyn CacheAdapter>,
+        pending_requests: Arc<RwLock<HashMap<String, PendingRequest>>>,
+        shutdown_notify: Arc<tokio::sync::Notify>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let websocket = accept_async(stream).await?;
+        let (mut ws_sender, mut ws_receiver) = websocket.split();
+
+        let (msg_sender, mut msg_receiver) = mpsc::unbounded_channel();
+        let client_id = Uuid::new_v4().to_string();
+
+        // Handle outgoing messages; on shutdown, send a Close frame instead of
+        // waiting for the channel to drain naturally. Spawned directly rather
+        // than through `BackgroundRunner::submit` - this client's own job is
+        // already holding one pool permit for its whole lifetime, and waiting
+        // on a second permit here for as long as the connection lasts would
+        // double the pool slots one client ties up.
+        let sender_handle = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    message = msg_receiver.recv() => {
+                        match message {
+                            Some(message) => {
+                                let json = serde_json::to_string(&message).unwrap();
+                                if let Err(e) = ws_sender.send(Message::Text(json)).await {
+                                    error!("Failed to send message to client: {}", e);
+                                    break;
+                                }
+                            }
+                            None => break,
+                        }
+                    }
+                    _ = shutdown_notify.notified() => {
+                        let _ = ws_sender.send(Message::Close(None)).await;
+                        break;
+                    }
+                }
+            }
+        });
+        let sender_abort = sender_handle.abort_handle();
+
+        // Handle incoming messages, racing each read against a full-bridge
+        // shutdown - otherwise an idle or unresponsive client (exactly what
+        // the heartbeat checker exists to catch) never sends its own Close
+        // or error, `ws_receiver.next()` never resolves, and `start()`'s
+        // `runner.shutdown().await` blocks forever waiting on this job.
+        let mut shutting_down = false;
+        loop {
+            tokio::select! {
+                msg = ws_receiver.next() => {
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            if let Ok(android_msg) = serde_json::from_str::<AndroidMessage>(&text) {
+                                Self::process_message(
+                                    android_msg,
+                                    &client_id,
+                                    clients.clone(),
+                                    config.clone(),
+                                    backup_manager.clone(),
+                                    fs_monitor.clone(),
+                                    kernel_interface.clone(),
+                                    pairing.clone(),
+                                    cache.clone(),
+                                    pending_requests.clone(),
+                                    msg_sender.clone(),
+                                ).await;
+                            } else {
+                                error!("Failed to parse Android message: {}", text);
+                            }
+                        }
+                        Some(Ok(Message::Close(_))) => {
+                            info!("Client {} disconnected", client_id);
+                            break;
+                        }
+                        Some(Err(e)) => {
+                            error!("WebSocket error: {}", e);
+                            break;
+                        }
+                        Some(_) => {}
+                        None => break,
+                    }
+                }
+                _ = shutdown_notify.notified() => {
+                    info!("Client {} read loop stopping for bridge shutdown", client_id);
+                    shutting_down = true;
+                    break;
+                }
+            }
+        }
+
+        // Cleanup. On a normal disconnect the sender task no longer has any
+        // client to send to, so it's aborted outright; on a bridge-wide
+        // shutdown it was woken by the same `shutdown_notify` this loop just
+        // reacted to and is already on its way to sending a Close frame and
+        // exiting, so it's awaited instead - bounded by a grace period in
+        // case the peer's socket is too wedged to even accept that frame.
+        clients.write().await.remove(&client_id);
+        if shutting_down {
+            match tokio::time::timeout(SENDER_SHUTDOWN_GRACE, sender_handle).await {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) if !e.is_cancelled() => warn!("Sender task for client {} failed: {}", client_id, e),
+                Ok(Err(_)) => {}
+                Err(_) => warn!("Sender task for client {} did not finish within the shutdown grace period", client_id),
+            }
+        } else {
+            sender_abort.abort();
+        }
+
+        Ok(())
+    }
+
+    async fn process_message(
+        message: AndroidMessage,
+        client_id: &str,
+        clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
+        config: Arc<DaemonConfig>,
+        backup_manager: Arc<RwLock<BackupManager>>,
+        fs_monitor: Arc<RwLock<FileSystemMonitor>>,
+        kernel_interface: Arc<KernelInterface>,
+        pairing: Arc<PairingManager>,
+        cache: Arc<dyn CacheAdapter>,
+        pending_requests: Arc<RwLock<HashMap<String, PendingRequest>>>,
+        sender: mpsc::UnboundedSender<AndroidMessage>,
+    ) {
+        debug!("Processing message: {:?}", message.message_type);
+        let request_id = message.id.clone();
+
+        match message.message_type {
+            AndroidMessageType::Auth { token, device_id } => {
+                // A pairing secret is single-use and wins if it still matches;
+                // otherwise fall back to treating `token` as an already-issued
+                // per-device credential.
+                let (success, device_token) = match pairing.exchange(&token, &device_id).await {
+                    Some(new_token) => (true, Some(new_token)),
+                    None => {
+                        let authenticated = pairing.authenticate(&token).await.as_deref() == Some(device_id.as_str());
+                        (authenticated, None)
+                    }
+                };
+
+                if success {
+                    info!("Client {} authenticated as device {}", client_id, device_id);
+                } else {
+                    warn!("Client {} failed authentication as device {}", client_id, device_id);
+                }
+
+                let response = AndroidMessage::reply_to(
+                    &request_id,
+                    AndroidMessageType::AuthResponse { success, device_id: device_id.clone(), device_token },
+                );
+                let _ = sender.send(response);
+            }
+
+            AndroidMessageType::BeginPairing { admin_token } => {
+                if admin_token != config.android.auth_token {
+                    warn!("Client {} attempted to begin pairing with an invalid admin token", client_id);
+                    let error_response = AndroidMessage::reply_to(
+                        &request_id,
+                        AndroidMessageType::Error { code: 1004, message: "Invalid admin token".to_string() },
+                    );
+                    let _ = sender.send(error_response);
+                    return;
+                }
+
+                match pairing.begin_pairing().await {
+                    Ok((qr_png_base64, expires_at)) => {
+                        let response = AndroidMessage::reply_to(&request_id, AndroidMessageType::PairingCode { qr_png_base64, expires_at });
+                        let _ = sender.send(response);
+                    }
+                    Err(e) => {
+                        error!("Failed to generate pairing code: {}", e);
+                        let error_response = AndroidMessage::reply_to(
+                            &request_id,
+                            AndroidMessageType::Error { code: 1004, message: format!("Failed to generate pairing code: {}", e) },
+                        );
+                        let _ = sender.send(error_response);
+                    }
+                }
+            }
+
+            AndroidMessageType::RevokeDevice { admin_token, device_id } => {
+                if admin_token != config.android.auth_token {
+                    warn!("Client {} attempted to revoke device {} with an invalid admin token", client_id, device_id);
+                    let error_response = AndroidMessage::reply_to(
+                        &request_id,
+                        AndroidMessageType::Error { code: 1004, message: "Invalid admin token".to_string() },
+                    );
+                    let _ = sender.send(error_response);
+                    return;
+                }
+
+                pairing.revoke(&device_id).await;
+                info!("Revoked pairing credential for device {}", device_id);
+                let response = AndroidMessage::reply_to(&request_id, AndroidMessageType::DeviceRevoked { device_id });
+                let _ = sender.send(response);
+            }
+
+            AndroidMessageType::GetSystemStatus => {
+                const SYSTEM_STATUS_CACHE_KEY: &str = "system_status";
+
+                let status = match cache.get_typed::<SystemStatusInfo>(SYSTEM_STATUS_CACHE_KEY).await {
+                    Some(status) => status,
+                    None => {
+                        let backup_manager = backup_manager.read().await;
+                        let status = SystemStatusInfo {
+                            daemon_uptime: 12345, // TODO: Calculate actual uptime
+                            active_backups: backup_manager.get_active_job_count().await,
+                            total_files_backed_up: backup_manager.get_total_files_backed_up().await,
+                            total_backup_size: backup_manager.get_total_backup_size().await,
+                            memory_usage: Self::get_memory_usage(),
+                            cpu_usage: Self::get_cpu_usage(),
+                            kernel_module_loaded: kernel_interface.is_loaded().await,
+                            services_status: Self::get_services_status().await,
+                        };
+                        cache.set_typed(
+                            SYSTEM_STATUS_CACHE_KEY,
+                            &status,
+                            Duration::from_secs(config.cache.default_ttl_secs),
+                        ).await;
+                        status
+                    }
+                };
+
+                let response = AndroidMessage::reply_to(&request_id, AndroidMessageType::SystemStatus { status });
+                let _ = sender.send(response);
+            }
+
+            AndroidMessageType::StartBackup { paths, options } => {
+                let backup_manager = backup_manager.write().await;
+                // Resolved/rejected from the background job once every path has
+                // been processed, so the caller gets a reply tagged with the
+                // original request id even though the backup itself runs async.
+                let pending_requests_for_completion = pending_requests.clone();
+                let on_complete = move |job_id: String, result: Result<(), String>| {
+                    tokio::spawn(async move {
+                        if let Some(pending) = pending_requests_for_completion.write().await.remove(&job_id) {
+                            let response = match result {
+                                Ok(()) => AndroidMessage::reply_to(
+                                    &pending.request_id,
+                                    AndroidMessageType::BackupComplete { job_id, success: true, details: "Backup completed".to_string() },
+                                ),
+                                Err(e) => AndroidMessage::reply_to(
+                                    &pending.request_id,
+                                    AndroidMessageType::Error { code: 1001, message: format!("Backup failed: {}", e) },
+                                ),
+                            };
+                            let _ = pending.sender.send(response);
+                        }
+                    });
+                };
+
+                match backup_manager.start_backup(paths, options, on_complete).await {
+                    Ok(job_id) => {
+                        info!("Started backup job: {}", job_id);
+                        pending_requests.write().await.insert(
+                            job_id,
+                            PendingRequest { request_id: request_id.clone(), sender: sender.clone() },
+                        );
+                    }
+                    Err(e) => {
+                        error!("Failed to start backup: {}", e);
+                        let error_response = AndroidMessage::reply_to(
+                            &request_id,
+                            AndroidMessageType::Error { code: 1001, message: format!("Failed to start backup: {}", e) },
+                        );
+                        let _ = sender.send(error_response);
+                    }
+                }
+            }
+
+            AndroidMessageType::ListFiles { path } => {
+                let cache_key = Self::list_files_cache_key(&path);
+                if let Some(files) = cache.get_typed::<Vec<FileInfo>>(&cache_key).await {
+                    let response = AndroidMessage::reply_to(&request_id, AndroidMessageType::FileList { files });
+                    let _ = sender.send(response);
+                    return;
+                }
+
+                match fs_monitor.read().await.list_files(&path).await {
+                    Ok(files) => {
+                        cache.set_typed(&cache_key, &files, Duration::from_secs(config.cache.default_ttl_secs)).await;
+                        let response = AndroidMessage::reply_to(&request_id, AndroidMessageType::FileList { files });
+                        let _ = sender.send(response);
+                    }
+                    Err(e) => {
+                        error!("Failed to list files: {}", e);
+                        let error_response = AndroidMessage::reply_to(
+                            &request_id,
+                            AndroidMessageType::Error { code: 1002, message: format!("Failed to list files: {}", e) },
+                        );
+                        let _ = sender.send(error_response);
+                    }
+                }
+            }
+
+            AndroidMessageType::FileChanged { path, change_type } => {
+                debug!("Invalidating cached listings under {} ({})", path, change_type);
+                Self::invalidate_listing_cache(&cache, &path).await;
+                cache.invalidate("system_status").await;
+            }
+
+            AndroidMessageType::CompactNow => {
+                let backup_manager = backup_manager.write().await;
+                match backup_manager.compact_now().await {
+                    Ok(job_id) => {
+                        info!("Started manual compaction job: {}", job_id);
+                        pending_requests.write().await.insert(
+                            job_id,
+                            PendingRequest { request_id: request_id.clone(), sender: sender.clone() },
+                        );
+                    }
+                    Err(e) => {
+                        error!("Failed to start compaction: {}", e);
+                        let error_response = AndroidMessage::reply_to(
+                            &request_id,
+                            AndroidMessageType::Error { code: 1001, message: format!("Failed to start compaction: {}", e) },
+                        );
+                        let _ = sender.send(error_response);
+                    }
+                }
+            }
+
+            AndroidMessageType::GetBackupHistory { path } => {
+                let backup_manager = backup_manager.read().await;
+                let versions = backup_manager
+                    .get_backup_history()
+                    .await
+                    .into_iter()
+                    .filter(|v| v.path == path)
+                    .map(|v| BackupVersionInfo {
+                        path: v.path,
+                        size: v.size,
+                        block_count: v.blocks.len(),
+                        created_at: v.created_at,
+                        is_delete_marker: v.kind == VersionKind::DeleteMarker,
+                    })
+                    .collect();
+
+                let response = AndroidMessage::reply_to(&request_id, AndroidMessageType::BackupHistory { versions });
+                let _ = sender.send(response);
+            }
+
+            AndroidMessageType::ListVersions { path } => {
+                let backup_manager = backup_manager.read().await;
+                let versions = backup_manager
+                    .list_versions(&path)
+                    .await
+                    .into_iter()
+                    .map(|v| BackupVersionInfo {
+                        path: v.path,
+                        size: v.size,
+                        block_count: v.blocks.len(),
+                        created_at: v.created_at,
+                        is_delete_marker: v.kind == VersionKind::DeleteMarker,
+                    })
+                    .collect();
+
+                let response = AndroidMessage::reply_to(&request_id, AndroidMessageType::VersionList { versions });
+                let _ = sender.send(response);
+            }
+
+            AndroidMessageType::RestoreFile { file_path, restore_path, version, as_of_timestamp } => {
+                let backup_manager = backup_manager.read().await;
+                let selector = match (version, as_of_timestamp) {
+                    (Some(index), _) => RestoreSelector::Version(index),
+                    (None, Some(timestamp)) => RestoreSelector::AsOf(timestamp),
+                    (None, None) => RestoreSelector::Latest,
+                };
+
+                match backup_manager.restore_version(&file_path, selector).await {
+                    Some(data) => match tokio::fs::write(&restore_path, &data).await {
+                        Ok(()) => {
+                            let response = AndroidMessage::reply_to(
+                                &request_id,
+                                AndroidMessageType::RestoreProgress { progress: 1.0, details: format!("Restored to {}", restore_path) },
+                            );
+                            let _ = sender.send(response);
+                        }
+                        Err(e) => {
+                            error!("Failed to write restored file {}: {}", restore_path, e);
+                            let error_response = AndroidMessage::reply_to(
+                                &request_id,
+                                AndroidMessageType::Error { code: 1003, message: format!("Failed to write restored file: {}", e) },
+                            );
+                            let _ = sender.send(error_response);
+                        }
+                    },
+                    None => {
+                        let error_response = AndroidMessage::reply_to(
+                            &request_id,
+                            AndroidMessageType::Error { code: 1003, message: format!("No restorable version of {} found", file_path) },
+                        );
+                        let _ = sender.send(error_response);
+                    }
+                }
+            }
+
+            AndroidMessageType::GetKernelStatus => {
+                let status = kernel_interface.get_status().await;
+                let response = AndroidMessage::reply_to(
+                    &request_id,
+                    AndroidMessageType::KernelStatus {
+                        loaded: status.loaded,
+                        version: status.version,
+                        features: status.features,
+                    },
+                );
+                let _ = sender.send(response);
+            }
+
+            _ => {
+                warn!("Unhandled message type: {:?}", message.message_type);
+            }
+        }
+    }
+
+    /// Cache key for a `ListFiles` response, also used as the invalidation
+    /// prefix for `FileChanged` - a change under `path` invalidates every
+    /// cached listing keyed at or below it.
+    fn list_files_cache_key(path: &str) -> String {
+        format!("list_files:{}", path)
+    }
+
+    /// Every ancestor directory of `path`, nearest parent first, with no
+    /// trailing slash (`/a/b/c.txt` yields `["/a/b", "/a"]`). A changed
+    /// path's own cache key already covers anything cached at or below it
+    /// via `list_files_cache_key`'s prefix match, but a child path is never
+    /// a prefix of its parent's key - so evicting a parent directory's
+    /// cached listing when one of its files changes needs an explicit walk
+    /// up the tree rather than relying on that prefix match alone.
+    fn path_ancestors(path: &str) -> Vec<String> {
+        let mut ancestors = Vec::new();
+        let mut current = path.trim_end_matches('/');
+        while let Some(idx) = current.rfind('/') {
+            current = &current[..idx];
+            if current.is_empty() {
+                break;
+            }
+            ancestors.push(current.to_string());
+        }
+        ancestors
+    }
+
+    /// Drops the cached `ListFiles` response for `path` itself and for every
+    /// ancestor directory whose listing would include it, so a single file
+    /// change can't leave a stale parent-directory listing behind.
+    async fn invalidate_listing_cache(cache: &Arc<dyn CacheAdapter>, path: &str) {
+        cache.invalidate(&Self::list_files_cache_key(path)).await;
+        for ancestor in Self::path_ancestors(path) {
+            cache.invalidate(&Self::list_files_cache_key(&ancestor)).await;
+        }
+    }
+
+    async fn check_client_heartbeats(
+        clients: Arc<RwLock<HashMap<String, AndroidClient>>>,
+        timeout_seconds: u64,
+    ) {
+        let mut clients_to_remove = Vec::new();
+        let timeout_duration = std::time::Duration::from_secs(timeout_seconds);
+        
+        {
+            let clients_read = clients.read().await;
+            for (client_id, client) in clients_read.iter() {
+                if client.last_heartbeat.elapsed() > timeout_duration {
+                    clients_to_remove.push(client_id.clone());
+                }
+            }
+        }
+
+        if !clients_to_remove.is_empty() {
+            let mut clients_write = clients.write().await;
+            for client_id in clients_to_remove {
+                warn!("Removing inactive client: {}", client_id);
+                clients_write.remove(&client_id);
+            }
+        }
+    }
+
+    fn get_memory_usage() -> u64 {
+        // TODO: Implement actual memory usage calculation
+        64 * 1024 * 1024 // 64MB placeholder
+    }
+
+    fn get_cpu_usage() -> f32 {
+        // TODO: Implement actual CPU usage calculation
+        15.5 // 15.5% placeholder
+    }
+
+    async fn get_services_status() -> HashMap<String, bool> {
+        // TODO: Implement actual service health checks
+        let mut status = HashMap::new();
+        status.insert("backup_engine".to_string(), true);
+        status.insert("storage_hal".to_string(), true);
+        status.insert("compression_engine".to_string(), true);
+        status.insert("encryption_service".to_string(), false);
+        status.insert("ml_optimizer".to_string(), true);
+        status
+    }
+
+    pub async fn broadcast_message(&self, message: AndroidMessage) {
+        let clients = self.clients.read().await;
+        for (_, client) in clients.iter() {
+            let _ = client.message_sender.send(message.clone());
+        }
+    }
+
+    pub async fn send_to_device(&self, device_id: &str, message: AndroidMessage) -> bool {
+        let clients = self.clients.read().await;
+        if let Some(client) = clients.get(device_id) {
+            client.message_sender.send(message).is_ok()
+        } else {
+            false
+        }
+    }
+
+    /// Invalidates cached listings/status affected by a filesystem change and
+    /// tells connected clients about it. There's no live file-watching loop
+    /// wired up yet (`FileSystemMonitor::changed_since` is still a stub), so
+    /// this is the entry point for whenever one lands.
+    pub async fn notify_file_changed(&self, path: &str, change_type: &str) {
+        Self::invalidate_listing_cache(&self.cache, path).await;
+        self.cache.invalidate("system_status").await;
+        self.broadcast_message(AndroidMessage {
+            id: Uuid::new_v4().to_string(),
+            correlation_id: None,
+            message_type: AndroidMessageType::FileChanged { path: path.to_string(), change_type: change_type.to_string() },
+            payload: serde_json::Value::Null,
+            timestamp: AndroidMessage::now(),
+        }).await;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,86 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::task::{AbortHandle, JoinHandle};
+use tracing::{debug, warn};
+
+/// Relative scheduling weight for a submitted job. Currently informational -
+/// it is recorded for observability and future priority-queue work rather
+/// than affecting dispatch order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JobPriority {
+    Low,
+    Normal,
+    High,
+}
+
+/// Owns a bounded worker pool and the set of in-flight task handles spawned
+/// through it, so the daemon can report how much background work is running
+/// and stop all of it deterministically on shutdown - replacing scattered,
+/// untracked `tokio::spawn` calls across the bridge and backup manager.
+pub struct BackgroundRunner {
+    semaphore: Arc<Semaphore>,
+    handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    active_jobs: Arc<AtomicUsize>,
+}
+
+impl BackgroundRunner {
+    /// Creates a runner whose worker pool admits at most `max_concurrent`
+    /// jobs at a time; submissions beyond that simply wait for a slot.
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            handles: Arc::new(Mutex::new(Vec::new())),
+            active_jobs: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Schedules `task` onto the worker pool, waiting for a free slot if the
+    /// pool is saturated, and records its `JoinHandle` so `shutdown` can wait
+    /// for it to finish. Returns an `AbortHandle` for callers that need to
+    /// cancel the job early.
+    pub async fn submit<F>(&self, name: impl Into<String>, priority: JobPriority, task: F) -> AbortHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("background runner semaphore should never be closed");
+        let active_jobs = self.active_jobs.clone();
+        active_jobs.fetch_add(1, Ordering::SeqCst);
+        debug!("Scheduling background job '{}' (priority {:?})", name, priority);
+
+        let handle = tokio::spawn(async move {
+            let _permit = permit;
+            task.await;
+            active_jobs.fetch_sub(1, Ordering::SeqCst);
+        });
+        let abort_handle = handle.abort_handle();
+
+        self.handles.lock().await.push(handle);
+        abort_handle
+    }
+
+    pub fn active_job_count(&self) -> usize {
+        self.active_jobs.load(Ordering::SeqCst)
+    }
+
+    /// Waits for every job handle recorded so far to finish. New submissions
+    /// racing with shutdown are not tracked; callers should stop submitting
+    /// before calling this.
+    pub async fn shutdown(&self) {
+        let handles = std::mem::take(&mut *self.handles.lock().await);
+        for handle in handles {
+            if let Err(e) = handle.await {
+                if !e.is_cancelled() {
+                    warn!("Background job failed during shutdown: {}", e);
+                }
+            }
+        }
+    }
+}
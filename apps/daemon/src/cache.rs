@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::config::{CacheBackend, CacheConfig};
+
+/// A pluggable cache for request/response data that's expensive to
+/// recompute but cheap to go briefly stale - file listings and system
+/// status, currently. Keys are plain strings so callers can namespace them
+/// (`"list_files:/sdcard"`) and `invalidate` treats its argument as a
+/// prefix, so one filesystem change can drop every cached listing under it.
+#[async_trait]
+pub trait CacheAdapter: Send + Sync {
+    async fn get(&self, key: &str) -> Option<Vec<u8>>;
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration);
+    async fn invalidate(&self, prefix: &str);
+}
+
+impl dyn CacheAdapter {
+    /// Convenience wrapper around `get`/`set` for callers that just want to
+    /// cache a serializable value without touching raw bytes.
+    pub async fn get_typed<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let bytes = self.get(key).await?;
+        bincode::deserialize(&bytes).ok()
+    }
+
+    pub async fn set_typed<T: Serialize + Sync>(&self, key: &str, value: &T, ttl: Duration) {
+        match bincode::serialize(value) {
+            Ok(bytes) => self.set(key, bytes, ttl).await,
+            Err(e) => warn!("Failed to serialize value for cache key {}: {}", key, e),
+        }
+    }
+}
+
+struct CacheEntry {
+    value: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// Embedded, single-process cache - a `HashMap` behind an `RwLock` with a
+/// per-entry expiry. No eviction beyond lazy expiry-on-read; fine for the
+/// request volumes this daemon sees.
+pub struct InMemoryCache {
+    entries: RwLock<HashMap<String, CacheEntry>>,
+}
+
+impl InMemoryCache {
+    pub fn new() -> Self {
+        Self { entries: RwLock::new(HashMap::new()) }
+    }
+}
+
+#[async_trait]
+impl CacheAdapter for InMemoryCache {
+    async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let entries = self.entries.read().await;
+        let entry = entries.get(key)?;
+        if entry.expires_at < Instant::now() {
+            return None;
+        }
+        Some(entry.value.clone())
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) {
+        self.entries.write().await.insert(
+            key.to_string(),
+            CacheEntry { value, expires_at: Instant::now() + ttl },
+        );
+    }
+
+    async fn invalidate(&self, prefix: &str) {
+        self.entries.write().await.retain(|key, _| !key.starts_with(prefix));
+    }
+}
+
+/// Cache backed by a shared Redis instance, for deployments running more
+/// than one daemon against the same device. TTL is enforced by Redis
+/// itself (`SET ... EX`); `invalidate` scans for matching keys since Redis
+/// has no native prefix-delete.
+pub struct RedisCache {
+    client: redis::Client,
+}
+
+impl RedisCache {
+    pub fn new(redis_url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self { client: redis::Client::open(redis_url)? })
+    }
+}
+
+#[async_trait]
+impl CacheAdapter for RedisCache {
+    async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut conn = self.client.get_multiplexed_async_connection().await.ok()?;
+        redis::cmd("GET").arg(key).query_async(&mut conn).await.ok()?
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) {
+        let Ok(mut conn) = self.client.get_multiplexed_async_connection().await else {
+            warn!("Failed to connect to Redis while setting cache key {}", key);
+            return;
+        };
+        let result: redis::RedisResult<()> = redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut conn)
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to set cache key {} in Redis: {}", key, e);
+        }
+    }
+
+    async fn invalidate(&self, prefix: &str) {
+        let Ok(mut conn) = self.client.get_multiplexed_async_connection().await else {
+            warn!("Failed to connect to Redis while invalidating prefix {}", prefix);
+            return;
+        };
+        let keys: redis::RedisResult<Vec<String>> = redis::cmd("KEYS")
+            .arg(format!("{}*", prefix))
+            .query_async(&mut conn)
+            .await;
+        match keys {
+            Ok(keys) if !keys.is_empty() => {
+                debug!("Invalidating {} Redis key(s) under prefix {}", keys.len(), prefix);
+                let _: redis::RedisResult<()> = redis::cmd("DEL").arg(keys).query_async(&mut conn).await;
+            }
+            Ok(_) => {}
+            Err(e) => warn!("Failed to scan Redis keys under prefix {}: {}", prefix, e),
+        }
+    }
+}
+
+/// Builds the configured cache backend.
+pub fn build_cache_adapter(config: &CacheConfig) -> Result<Arc<dyn CacheAdapter>, Box<dyn std::error::Error>> {
+    match config.backend {
+        CacheBackend::Memory => Ok(Arc::new(InMemoryCache::new())),
+        CacheBackend::Redis => {
+            let url = config.redis_url.as_deref().ok_or("Redis cache backend selected but redis_url is not set")?;
+            Ok(Arc::new(RedisCache::new(url)?))
+        }
+    }
+}
@@ -11,6 +11,7 @@ pub struct DaemonConfig {
     pub filesystem: FilesystemConfig,
     pub kernel: KernelConfig,
     pub logging: LoggingConfig,
+    pub cache: CacheConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,18 +29,79 @@ pub struct AndroidConfig {
     pub auth_token: String,
     pub max_connections: u16,
     pub heartbeat_interval: u64,
+    pub tls_enabled: bool,
+    pub cert_path: Option<PathBuf>,
+    pub key_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupConfig {
     pub backup_root: PathBuf,
+    /// Where the embedded metadata store (job state, version chains, block
+    /// index) is persisted, so backups survive a daemon restart.
+    pub metadata_db_path: PathBuf,
     pub chunk_size: usize,
+    /// Normalized 0-9 profile the compression engine maps onto each codec's
+    /// native scale (see `CompressionLevel`) - 0-2 favors speed, 9 favors
+    /// ratio, independent of which `CompressionType`/`CompressionPolicyConfig`
+    /// ends up choosing the codec.
     pub compression_level: u8,
+    /// Minimum fractional size reduction a compressed block must achieve to
+    /// be stored over the plain bytes - see the compression engine's
+    /// `encode_block`. Below this, already-compressed or encrypted chunks
+    /// round-trip as plain data instead of being stored larger.
+    pub min_compression_ratio: f64,
+    pub chunking: ChunkingConfig,
+    pub dictionary: DictionaryConfig,
+    pub compression_policy: CompressionPolicyConfig,
     pub encryption: EncryptionConfig,
     pub retention: RetentionConfig,
     pub services: ServiceEndpoints,
 }
 
+/// Min/avg/max size bounds for the compression engine's FastCDC chunker.
+/// `avg_size` defaults to `chunk_size` - fixed-size chunking defeats dedup
+/// whenever bytes shift, so the engine cuts at content-defined boundaries
+/// instead, but still needs a target size to tune toward.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkingConfig {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+/// Controls the compression engine's zstd dictionary training for the many
+/// small, structurally similar files under `/data/data` (SQLite DBs,
+/// SharedPreferences XML, JSON) that compress poorly on their own. See the
+/// compression engine's `dictionary` module for the training and
+/// compress/decompress-with-dictionary logic this config feeds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DictionaryConfig {
+    pub enabled: bool,
+    pub max_dictionary_size: usize,
+}
+
+/// Governs the compression engine's `choose_algorithm` for a chunk. `Auto`
+/// samples the chunk and picks between lz4/zstd/brotli per
+/// `AutoCompressionPolicy`; `Forced` pins `forced_algorithm` for deployments
+/// that would rather trade ratio for predictable CPU than let the sampler
+/// decide.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompressionPolicyConfig {
+    pub mode: CompressionMode,
+    /// Only read when `mode` is `Forced` - one of "zstd", "lz4", "brotli",
+    /// "gzip", "xz" (see the compression engine's `CompressionType::from_str`).
+    pub forced_algorithm: Option<String>,
+    pub sample_size: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressionMode {
+    Auto,
+    Forced,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EncryptionConfig {
     pub enabled: bool,
@@ -89,6 +151,21 @@ pub struct LoggingConfig {
     pub max_files: u32,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    pub backend: CacheBackend,
+    /// Only required when `backend` is `Redis`.
+    pub redis_url: Option<String>,
+    pub default_ttl_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    Memory,
+    Redis,
+}
+
 impl Default for DaemonConfig {
     fn default() -> Self {
         Self {
@@ -104,11 +181,30 @@ impl Default for DaemonConfig {
                 auth_token: "default-token".to_string(),
                 max_connections: 10,
                 heartbeat_interval: 30,
+                tls_enabled: false,
+                cert_path: None,
+                key_path: None,
             },
             backup: BackupConfig {
                 backup_root: PathBuf::from("/data/backups"),
+                metadata_db_path: PathBuf::from("/data/backups/metadata.sled"),
                 chunk_size: 4 * 1024 * 1024, // 4MB
                 compression_level: 6,
+                min_compression_ratio: 0.05, // skip storing compressed form below a 5% saving
+                chunking: ChunkingConfig {
+                    min_size: 1024 * 1024,      // 1MB
+                    avg_size: 4 * 1024 * 1024,  // matches chunk_size
+                    max_size: 16 * 1024 * 1024, // 16MB
+                },
+                dictionary: DictionaryConfig {
+                    enabled: true,
+                    max_dictionary_size: 110 * 1024, // zstd's own default trainer target
+                },
+                compression_policy: CompressionPolicyConfig {
+                    mode: CompressionMode::Auto,
+                    forced_algorithm: None,
+                    sample_size: 16 * 1024,
+                },
                 encryption: EncryptionConfig {
                     enabled: true,
                     algorithm: "AES-256-GCM".to_string(),
@@ -155,6 +251,11 @@ impl Default for DaemonConfig {
                 max_file_size: 10 * 1024 * 1024, // 10MB
                 max_files: 5,
             },
+            cache: CacheConfig {
+                backend: CacheBackend::Memory,
+                redis_url: None,
+                default_ttl_secs: 30,
+            },
         }
     }
 }
@@ -205,10 +306,39 @@ impl DaemonConfig {
             return Err("Compression level must be between 0-9".to_string());
         }
 
+        if !(0.0..=1.0).contains(&self.backup.min_compression_ratio) {
+            return Err("min_compression_ratio must be between 0.0 and 1.0".to_string());
+        }
+
+        let chunking = &self.backup.chunking;
+        if !(chunking.min_size < chunking.avg_size && chunking.avg_size < chunking.max_size) {
+            return Err("Chunking sizes must satisfy min_size < avg_size < max_size".to_string());
+        }
+
+        if self.backup.dictionary.enabled && self.backup.dictionary.max_dictionary_size == 0 {
+            return Err("Dictionary max_dictionary_size cannot be 0 when dictionary coding is enabled".to_string());
+        }
+
+        let policy = &self.backup.compression_policy;
+        if policy.mode == CompressionMode::Forced {
+            match policy.forced_algorithm.as_deref() {
+                Some("zstd") | Some("lz4") | Some("brotli") | Some("gzip") | Some("xz") => {}
+                _ => return Err("Forced compression mode requires forced_algorithm to be one of zstd/lz4/brotli/gzip/xz".to_string()),
+            }
+        }
+
         if self.filesystem.watch_paths.is_empty() {
             return Err("At least one filesystem watch path must be configured".to_string());
         }
 
+        if self.android.tls_enabled && (self.android.cert_path.is_none() || self.android.key_path.is_none()) {
+            return Err("Android bridge TLS requires both cert_path and key_path".to_string());
+        }
+
+        if self.cache.backend == CacheBackend::Redis && self.cache.redis_url.is_none() {
+            return Err("Redis cache backend requires redis_url".to_string());
+        }
+
         Ok(())
     }
 }
\ No newline at end of file
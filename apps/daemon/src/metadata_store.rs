@@ -0,0 +1,50 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sled::{Db, Tree};
+use std::path::Path;
+use tracing::info;
+
+/// Embedded on-disk store backing daemon state that must survive a restart -
+/// the block-hash index, per-path version chains, and job records each get
+/// their own `sled` tree so `BackupManager` can rehydrate without rescanning
+/// anything. Values are `bincode`-encoded; keys are plain UTF-8 strings.
+pub struct MetadataStore {
+    db: Db,
+}
+
+impl MetadataStore {
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let db = sled::open(path)?;
+        info!("Metadata store opened at {}", path.display());
+        Ok(Self { db })
+    }
+
+    pub fn tree(&self, name: &str) -> Result<Tree, Box<dyn std::error::Error>> {
+        Ok(self.db.open_tree(name)?)
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, tree: &Tree, key: &str) -> Result<Option<T>, Box<dyn std::error::Error>> {
+        match tree.get(key)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put<T: Serialize>(&self, tree: &Tree, key: &str, value: &T) -> Result<(), Box<dyn std::error::Error>> {
+        let bytes = bincode::serialize(value)?;
+        tree.insert(key, bytes)?;
+        Ok(())
+    }
+
+    /// Deserializes every value in `tree`, paired with its key. Used at
+    /// startup to rehydrate a whole tree into an in-memory map.
+    pub fn iter<T: DeserializeOwned>(&self, tree: &Tree) -> Result<Vec<(String, T)>, Box<dyn std::error::Error>> {
+        let mut out = Vec::new();
+        for entry in tree.iter() {
+            let (key, bytes) = entry?;
+            let key = String::from_utf8_lossy(&key).into_owned();
+            out.push((key, bincode::deserialize(&bytes)?));
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,117 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use qrcode::QrCode;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// How long a pairing code stays scannable before it's useless - short
+/// enough that a code left on screen isn't a standing credential.
+const PAIRING_TTL_SECS: u64 = 300;
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// A per-device credential minted the first time a device exchanges a
+/// pairing secret. Replaces the single shared `auth_token` - each device
+/// gets its own token that can be revoked without affecting the rest.
+#[derive(Debug, Clone)]
+pub struct DeviceCredential {
+    pub device_id: String,
+    pub token: String,
+    pub paired_at: u64,
+    pub revoked: bool,
+}
+
+/// Tracks in-flight pairing secrets and issued per-device credentials.
+/// A pairing secret is single-use: the first device to exchange it wins,
+/// and it's removed whether or not the exchange succeeds.
+pub struct PairingManager {
+    pending_secrets: Arc<RwLock<HashMap<String, u64>>>,
+    credentials: Arc<RwLock<HashMap<String, DeviceCredential>>>,
+    token_to_device: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl PairingManager {
+    pub fn new() -> Self {
+        Self {
+            pending_secrets: Arc::new(RwLock::new(HashMap::new())),
+            credentials: Arc::new(RwLock::new(HashMap::new())),
+            token_to_device: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Generates a new pairing secret, renders it as a base64-encoded QR
+    /// code PNG, and returns it alongside its expiry. The secret itself is
+    /// never sent back to the caller - only the rendered code.
+    pub async fn begin_pairing(&self) -> Result<(String, u64), Box<dyn std::error::Error>> {
+        let secret = uuid::Uuid::new_v4().to_string();
+        let expires_at = now_secs() + PAIRING_TTL_SECS;
+        self.pending_secrets.write().await.insert(secret.clone(), expires_at);
+
+        let qr_png_base64 = Self::render_qr_png(&secret)?;
+        info!("Pairing code generated, expires at {}", expires_at);
+        Ok((qr_png_base64, expires_at))
+    }
+
+    fn render_qr_png(data: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let code = QrCode::new(data)?;
+        let image = code.render::<image::Luma<u8>>().build();
+
+        let mut png_bytes = Vec::new();
+        image::DynamicImage::ImageLuma8(image).write_to(&mut Cursor::new(&mut png_bytes), image::ImageFormat::Png)?;
+
+        Ok(BASE64.encode(png_bytes))
+    }
+
+    /// Consumes `secret` if it's still pending and unexpired, minting a new
+    /// per-device token bound to `device_id`. Returns `None` (and leaves any
+    /// existing credentials untouched) if the secret is unknown, expired, or
+    /// already used.
+    pub async fn exchange(&self, secret: &str, device_id: &str) -> Option<String> {
+        let expires_at = self.pending_secrets.write().await.remove(secret)?;
+        if now_secs() > expires_at {
+            return None;
+        }
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let credential = DeviceCredential {
+            device_id: device_id.to_string(),
+            token: token.clone(),
+            paired_at: now_secs(),
+            revoked: false,
+        };
+
+        self.token_to_device.write().await.insert(token.clone(), device_id.to_string());
+        self.credentials.write().await.insert(device_id.to_string(), credential);
+        info!("Device {} paired with a new credential", device_id);
+        Some(token)
+    }
+
+    /// Validates a previously-issued per-device token, returning the device
+    /// id it's bound to if the token is known and hasn't been revoked.
+    pub async fn authenticate(&self, token: &str) -> Option<String> {
+        let device_id = self.token_to_device.read().await.get(token).cloned()?;
+        let credentials = self.credentials.read().await;
+        let credential = credentials.get(&device_id)?;
+        if credential.revoked || credential.token != token {
+            return None;
+        }
+        Some(device_id)
+    }
+
+    /// Revokes `device_id`'s credential so its token no longer authenticates.
+    /// The device must be re-paired to regain access.
+    pub async fn revoke(&self, device_id: &str) {
+        if let Some(credential) = self.credentials.write().await.get_mut(device_id) {
+            credential.revoked = true;
+            self.token_to_device.write().await.remove(&credential.token);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::compression::{
+    compress_brotli, compress_gzip, compress_lz4, compress_xz, compress_zstd, decompress_brotli,
+    decompress_gzip, decompress_lz4, decompress_xz, decompress_zstd, CompressionLevel, CompressionType,
+};
+use anyhow::Result;
+
+/// A pluggable compression backend, following the codec-interface design
+/// Parquet's compression module uses. `compress_data_with_level` and
+/// `decompress_data` dispatch through a `Box<dyn Codec>` from `create_codec`
+/// instead of matching on `CompressionType`. The enum's tag, name and frame
+/// mappings in `compression` and the streaming layer in `stream` still match
+/// on every variant, so a new codec (e.g. snappy) is wired in there as well.
+pub trait Codec: Send + Sync {
+    fn id(&self) -> CompressionType;
+
+    /// Compresses `input` at `level`, appending the result onto `out`
+    /// rather than returning a fresh `Vec` - lets callers reuse one buffer
+    /// across many shards instead of allocating per chunk.
+    fn compress(&self, input: &[u8], level: CompressionLevel, out: &mut Vec<u8>) -> Result<()>;
+
+    /// Decompresses `input`, appending onto `out`. `size_hint`, when given,
+    /// reserves capacity up front so the output doesn't reallocate as it
+    /// grows.
+    fn decompress(&self, input: &[u8], out: &mut Vec<u8>, size_hint: Option<usize>) -> Result<()>;
+}
+
+/// Looks up the `Codec` for `compression_type`. The sole match over
+/// `CompressionType` this module needs - callers hold a `Box<dyn Codec>`
+/// afterward and never match on the enum again.
+pub fn create_codec(compression_type: CompressionType) -> Box<dyn Codec> {
+    match compression_type {
+        CompressionType::Zstd => Box::new(ZstdCodec),
+        CompressionType::Lz4 => Box::new(Lz4Codec),
+        CompressionType::Brotli => Box::new(BrotliCodec),
+        CompressionType::Gzip => Box::new(GzipCodec),
+        CompressionType::Xz => Box::new(XzCodec),
+    }
+}
+
+struct ZstdCodec;
+impl Codec for ZstdCodec {
+    fn id(&self) -> CompressionType {
+        CompressionType::Zstd
+    }
+
+    fn compress(&self, input: &[u8], level: CompressionLevel, out: &mut Vec<u8>) -> Result<()> {
+        out.extend_from_slice(&compress_zstd(input, level.zstd_level())?);
+        Ok(())
+    }
+
+    fn decompress(&self, input: &[u8], out: &mut Vec<u8>, size_hint: Option<usize>) -> Result<()> {
+        if let Some(hint) = size_hint {
+            out.reserve(hint);
+        }
+        decompress_zstd(input, out)?;
+        Ok(())
+    }
+}
+
+struct Lz4Codec;
+impl Codec for Lz4Codec {
+    fn id(&self) -> CompressionType {
+        CompressionType::Lz4
+    }
+
+    fn compress(&self, input: &[u8], level: CompressionLevel, out: &mut Vec<u8>) -> Result<()> {
+        out.extend_from_slice(&compress_lz4(input, level.lz4_mode())?);
+        Ok(())
+    }
+
+    fn decompress(&self, input: &[u8], out: &mut Vec<u8>, size_hint: Option<usize>) -> Result<()> {
+        if let Some(hint) = size_hint {
+            out.reserve(hint);
+        }
+        decompress_lz4(input, out)?;
+        Ok(())
+    }
+}
+
+struct BrotliCodec;
+impl Codec for BrotliCodec {
+    fn id(&self) -> CompressionType {
+        CompressionType::Brotli
+    }
+
+    fn compress(&self, input: &[u8], level: CompressionLevel, out: &mut Vec<u8>) -> Result<()> {
+        out.extend_from_slice(&compress_brotli(input, level.brotli_quality())?);
+        Ok(())
+    }
+
+    fn decompress(&self, input: &[u8], out: &mut Vec<u8>, size_hint: Option<usize>) -> Result<()> {
+        if let Some(hint) = size_hint {
+            out.reserve(hint);
+        }
+        decompress_brotli(input, out)?;
+        Ok(())
+    }
+}
+
+struct GzipCodec;
+impl Codec for GzipCodec {
+    fn id(&self) -> CompressionType {
+        CompressionType::Gzip
+    }
+
+    fn compress(&self, input: &[u8], level: CompressionLevel, out: &mut Vec<u8>) -> Result<()> {
+        out.extend_from_slice(&compress_gzip(input, level.gzip_level())?);
+        Ok(())
+    }
+
+    fn decompress(&self, input: &[u8], out: &mut Vec<u8>, size_hint: Option<usize>) -> Result<()> {
+        if let Some(hint) = size_hint {
+            out.reserve(hint);
+        }
+        decompress_gzip(input, out)?;
+        Ok(())
+    }
+}
+
+struct XzCodec;
+impl Codec for XzCodec {
+    fn id(&self) -> CompressionType {
+        CompressionType::Xz
+    }
+
+    fn compress(&self, input: &[u8], level: CompressionLevel, out: &mut Vec<u8>) -> Result<()> {
+        out.extend_from_slice(&compress_xz(input, level.xz_preset())?);
+        Ok(())
+    }
+
+    fn decompress(&self, input: &[u8], out: &mut Vec<u8>, size_hint: Option<usize>) -> Result<()> {
+        if let Some(hint) = size_hint {
+            out.reserve(hint);
+        }
+        decompress_xz(input, out)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_codec_round_trips_every_type() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(50);
+
+        for compression_type in [
+            CompressionType::Zstd,
+            CompressionType::Lz4,
+            CompressionType::Brotli,
+            CompressionType::Gzip,
+            CompressionType::Xz,
+        ] {
+            let codec = create_codec(compression_type);
+            assert_eq!(codec.id().as_str(), compression_type.as_str());
+
+            let mut compressed = Vec::new();
+            codec.compress(&data, CompressionLevel::DEFAULT, &mut compressed).unwrap();
+
+            let mut decompressed = Vec::new();
+            codec.decompress(&compressed, &mut decompressed, Some(data.len())).unwrap();
+            assert_eq!(data.to_vec(), decompressed);
+        }
+    }
+
+    #[test]
+    fn test_decompress_reuses_caller_supplied_buffer() {
+        let data = b"hello world, hello world, hello world".to_vec();
+        let codec = create_codec(CompressionType::Zstd);
+
+        let mut compressed = Vec::new();
+        codec.compress(&data, CompressionLevel::DEFAULT, &mut compressed).unwrap();
+
+        let mut out = Vec::new();
+        out.extend_from_slice(b"prefix:");
+        codec.decompress(&compressed, &mut out, None).unwrap();
+        assert_eq!(out, [b"prefix:".as_slice(), data.as_slice()].concat());
+    }
+}
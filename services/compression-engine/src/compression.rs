@@ -32,94 +32,388 @@ impl CompressionType {
             CompressionType::Xz => "xz",
         }
     }
+
+    /// One-byte wire tag identifying this algorithm, written by
+    /// `compress_auto` so `decompress_auto` is self-describing.
+    fn tag(&self) -> u8 {
+        match self {
+            CompressionType::Zstd => 0,
+            CompressionType::Lz4 => 1,
+            CompressionType::Brotli => 2,
+            CompressionType::Gzip => 3,
+            CompressionType::Xz => 4,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(CompressionType::Zstd),
+            1 => Ok(CompressionType::Lz4),
+            2 => Ok(CompressionType::Brotli),
+            3 => Ok(CompressionType::Gzip),
+            4 => Ok(CompressionType::Xz),
+            _ => Err(anyhow!("Unknown compression algorithm tag: {}", tag)),
+        }
+    }
 }
 
-pub fn compress_data(data: &[u8], compression_type: CompressionType) -> Result<Vec<u8>> {
-    match compression_type {
-        CompressionType::Zstd => compress_zstd(data),
-        CompressionType::Lz4 => compress_lz4(data),
-        CompressionType::Brotli => compress_brotli(data),
-        CompressionType::Gzip => compress_gzip(data),
-        CompressionType::Xz => compress_xz(data),
+/// Normalized 0-9 compression level, mirroring `BackupConfig::compression_level`.
+/// `compress_data_with_level` maps this single knob onto each codec's own
+/// native scale (zstd 1-22, brotli quality 0-11, xz preset 0-9, lz4
+/// fast/default/high-compression) so operators can pick one "fast" vs "max"
+/// profile instead of memorizing five different scales. Mirrors the
+/// per-producer compression-level configuration pulsar-rs recently added,
+/// where the level is part of the codec options rather than fixed in code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionLevel(pub u8);
+
+impl CompressionLevel {
+    pub const FAST: CompressionLevel = CompressionLevel(1);
+    pub const DEFAULT: CompressionLevel = CompressionLevel(3);
+    pub const BEST: CompressionLevel = CompressionLevel(9);
+
+    fn clamped(self) -> u8 {
+        self.0.min(9)
     }
+
+    pub(crate) fn zstd_level(self) -> i32 {
+        // zstd's native scale is 1-22.
+        1 + (self.clamped() as i32 * 21) / 9
+    }
+
+    pub(crate) fn brotli_quality(self) -> u32 {
+        // brotli's native scale is 0-11.
+        (self.clamped() as u32 * 11) / 9
+    }
+
+    pub(crate) fn xz_preset(self) -> u32 {
+        // xz's native preset scale is already 0-9.
+        self.clamped() as u32
+    }
+
+    pub(crate) fn gzip_level(self) -> u32 {
+        self.clamped() as u32
+    }
+
+    pub(crate) fn lz4_mode(self) -> lz4::block::CompressionMode {
+        match self.clamped() {
+            0..=2 => lz4::block::CompressionMode::FAST(10 - self.clamped() as i32 * 3),
+            3..=5 => lz4::block::CompressionMode::DEFAULT,
+            level => lz4::block::CompressionMode::HIGHCOMPRESSION(level as i32),
+        }
+    }
+}
+
+pub fn compress_data(data: &[u8], compression_type: CompressionType) -> Result<Vec<u8>> {
+    compress_data_with_level(data, compression_type, CompressionLevel::DEFAULT)
+}
+
+/// Same as `compress_data`, but maps the caller's `level` onto the chosen
+/// codec's native scale via `CompressionLevel` instead of always using
+/// `CompressionLevel::DEFAULT`. Dispatches through `create_codec`.
+pub fn compress_data_with_level(data: &[u8], compression_type: CompressionType, level: CompressionLevel) -> Result<Vec<u8>> {
+    let codec = crate::codec::create_codec(compression_type);
+    let mut out = Vec::new();
+    codec.compress(data, level, &mut out)?;
+    Ok(out)
 }
 
 pub fn decompress_data(data: &[u8], compression_type: CompressionType) -> Result<Vec<u8>> {
-    match compression_type {
-        CompressionType::Zstd => decompress_zstd(data),
-        CompressionType::Lz4 => decompress_lz4(data),
-        CompressionType::Brotli => decompress_brotli(data),
-        CompressionType::Gzip => decompress_gzip(data),
-        CompressionType::Xz => decompress_xz(data),
-    }
+    let codec = crate::codec::create_codec(compression_type);
+    let mut out = Vec::new();
+    codec.decompress(data, &mut out, None)?;
+    Ok(out)
 }
 
-fn compress_zstd(data: &[u8]) -> Result<Vec<u8>> {
-    let compressed = zstd::encode_all(data, 3)?;
+pub(crate) fn compress_zstd(data: &[u8], level: i32) -> Result<Vec<u8>> {
+    let compressed = zstd::encode_all(data, level)?;
     Ok(compressed)
 }
 
-fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>> {
-    let decompressed = zstd::decode_all(data)?;
-    Ok(decompressed)
+/// Decompresses into the caller-supplied `out` buffer via zstd's streaming
+/// `Read` decoder, rather than `zstd::decode_all`'s own fresh `Vec` - so
+/// `Codec::decompress` callers genuinely avoid the extra allocate-then-copy
+/// a free function returning `Vec<u8>` would force on them.
+pub(crate) fn decompress_zstd(data: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    let mut decoder = zstd::stream::Decoder::new(data)?;
+    decoder.read_to_end(out)?;
+    Ok(())
 }
 
-fn compress_lz4(data: &[u8]) -> Result<Vec<u8>> {
-    let compressed = lz4::block::compress(data, Some(lz4::block::CompressionMode::HIGHCOMPRESSION(9)), true)?;
+pub(crate) fn compress_lz4(data: &[u8], mode: lz4::block::CompressionMode) -> Result<Vec<u8>> {
+    let compressed = lz4::block::compress(data, Some(mode), true)?;
     Ok(compressed)
 }
 
-fn decompress_lz4(data: &[u8]) -> Result<Vec<u8>> {
+/// Unlike the other codecs, the `lz4` crate's block API only hands back a
+/// freshly allocated `Vec<u8>` - there's no streaming `Read` decoder and no
+/// safe way to learn the decompressed size ahead of calling it, so this
+/// still pays one intermediate allocation before copying into `out`.
+pub(crate) fn decompress_lz4(data: &[u8], out: &mut Vec<u8>) -> Result<()> {
     let decompressed = lz4::block::decompress(data, None)?;
-    Ok(decompressed)
+    out.extend_from_slice(&decompressed);
+    Ok(())
 }
 
-fn compress_brotli(data: &[u8]) -> Result<Vec<u8>> {
+pub(crate) fn compress_brotli(data: &[u8], quality: u32) -> Result<Vec<u8>> {
     let mut compressed = Vec::new();
-    let mut encoder = brotli::CompressorWriter::new(&mut compressed, 4096, 6, 22);
+    let mut encoder = brotli::CompressorWriter::new(&mut compressed, 4096, quality, 22);
     encoder.write_all(data)?;
     drop(encoder);
     Ok(compressed)
 }
 
-fn decompress_brotli(data: &[u8]) -> Result<Vec<u8>> {
-    let mut decompressed = Vec::new();
+pub(crate) fn decompress_brotli(data: &[u8], out: &mut Vec<u8>) -> Result<()> {
     let mut decoder = brotli::Decompressor::new(data, 4096);
-    decoder.read_to_end(&mut decompressed)?;
-    Ok(decompressed)
+    decoder.read_to_end(out)?;
+    Ok(())
 }
 
-fn compress_gzip(data: &[u8]) -> Result<Vec<u8>> {
+pub(crate) fn compress_gzip(data: &[u8], level: u32) -> Result<Vec<u8>> {
     use flate2::{write::GzEncoder, Compression};
-    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level));
     encoder.write_all(data)?;
     let compressed = encoder.finish()?;
     Ok(compressed)
 }
 
-fn decompress_gzip(data: &[u8]) -> Result<Vec<u8>> {
+pub(crate) fn decompress_gzip(data: &[u8], out: &mut Vec<u8>) -> Result<()> {
     use flate2::read::GzDecoder;
     let mut decoder = GzDecoder::new(data);
-    let mut decompressed = Vec::new();
-    decoder.read_to_end(&mut decompressed)?;
-    Ok(decompressed)
+    decoder.read_to_end(out)?;
+    Ok(())
 }
 
-fn compress_xz(data: &[u8]) -> Result<Vec<u8>> {
+pub(crate) fn compress_xz(data: &[u8], preset: u32) -> Result<Vec<u8>> {
     let mut compressed = Vec::new();
-    let mut encoder = xz2::write::XzEncoder::new(&mut compressed, 6);
+    let mut encoder = xz2::write::XzEncoder::new(&mut compressed, preset);
     encoder.write_all(data)?;
     encoder.finish()?;
     Ok(compressed)
 }
 
-fn decompress_xz(data: &[u8]) -> Result<Vec<u8>> {
+pub(crate) fn decompress_xz(data: &[u8], out: &mut Vec<u8>) -> Result<()> {
     let mut decoder = xz2::read::XzDecoder::new(data);
-    let mut decompressed = Vec::new();
-    decoder.read_to_end(&mut decompressed)?;
+    decoder.read_to_end(out)?;
+    Ok(())
+}
+
+/// Wire tag for `compress_framed`'s header. Mirrors Garage's
+/// `DataBlockHeader` (Plain vs Compressed) but carries the specific codec,
+/// so a stored blob is self-describing even after the in-memory
+/// `CompressionType` the caller used has been forgotten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum FrameTag {
+    Plain = 0,
+    Zstd = 1,
+    Lz4 = 2,
+    Brotli = 3,
+    Gzip = 4,
+    Xz = 5,
+}
+
+impl FrameTag {
+    fn from_compression_type(t: CompressionType) -> Self {
+        match t {
+            CompressionType::Zstd => FrameTag::Zstd,
+            CompressionType::Lz4 => FrameTag::Lz4,
+            CompressionType::Brotli => FrameTag::Brotli,
+            CompressionType::Gzip => FrameTag::Gzip,
+            CompressionType::Xz => FrameTag::Xz,
+        }
+    }
+
+    fn to_compression_type(self) -> Option<CompressionType> {
+        match self {
+            FrameTag::Plain => None,
+            FrameTag::Zstd => Some(CompressionType::Zstd),
+            FrameTag::Lz4 => Some(CompressionType::Lz4),
+            FrameTag::Brotli => Some(CompressionType::Brotli),
+            FrameTag::Gzip => Some(CompressionType::Gzip),
+            FrameTag::Xz => Some(CompressionType::Xz),
+        }
+    }
+
+    fn from_u8(tag: u8) -> Result<Self> {
+        match tag {
+            0 => Ok(FrameTag::Plain),
+            1 => Ok(FrameTag::Zstd),
+            2 => Ok(FrameTag::Lz4),
+            3 => Ok(FrameTag::Brotli),
+            4 => Ok(FrameTag::Gzip),
+            5 => Ok(FrameTag::Xz),
+            _ => Err(anyhow!("Unknown frame tag: {}", tag)),
+        }
+    }
+}
+
+/// LEB128-encodes `value` onto `buf`.
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            break;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+/// Reverses `write_varint`, returning the decoded value and how many bytes
+/// of `buf` it consumed.
+fn read_varint(buf: &[u8]) -> Result<(u64, usize)> {
+    let mut value: u64 = 0;
+    for (i, &byte) in buf.iter().enumerate() {
+        value |= ((byte & 0x7f) as u64) << (7 * i as u32);
+        if byte & 0x80 == 0 {
+            return Ok((value, i + 1));
+        }
+    }
+    Err(anyhow!("Truncated varint"))
+}
+
+/// Compresses `data` with `compression_type` and wraps it in a
+/// self-describing frame: a one-byte algorithm tag followed by a varint of
+/// the original uncompressed length, followed by the payload. If the
+/// compressed form isn't actually smaller, the frame falls back to a
+/// `Plain` tag over the original bytes instead of paying for a codec
+/// round-trip on incompressible data.
+///
+/// Unlike `encode_block`/`decode_block`, which need the caller to supply
+/// the same `CompressionType` at decode time, `decompress_framed` reads the
+/// codec straight out of the header.
+pub fn compress_framed(data: &[u8], compression_type: CompressionType) -> Result<Vec<u8>> {
+    let compressed = compress_data(data, compression_type)?;
+
+    let (tag, payload): (FrameTag, &[u8]) = if compressed.len() < data.len() {
+        (FrameTag::from_compression_type(compression_type), &compressed)
+    } else {
+        (FrameTag::Plain, data)
+    };
+
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(tag as u8);
+    write_varint(&mut frame, data.len() as u64);
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
+
+/// Reverses `compress_framed`: reads the header to find the codec and
+/// original length, then dispatches to the matching decoder (or returns the
+/// payload as-is for a `Plain` frame).
+pub fn decompress_framed(frame: &[u8]) -> Result<Vec<u8>> {
+    let (&tag_byte, rest) = frame.split_first().ok_or_else(|| anyhow!("Compressed frame is empty"))?;
+    let tag = FrameTag::from_u8(tag_byte)?;
+    let (original_len, consumed) = read_varint(rest)?;
+    let payload = &rest[consumed..];
+
+    let decompressed = match tag.to_compression_type() {
+        None => payload.to_vec(),
+        Some(compression_type) => decompress_data(payload, compression_type)?,
+    };
+
+    if decompressed.len() as u64 != original_len {
+        return Err(anyhow!(
+            "Frame header declared {} bytes but decoded {}",
+            original_len,
+            decompressed.len()
+        ));
+    }
     Ok(decompressed)
 }
 
+/// One-byte discriminant prefixed to an encoded block so `decode_block`
+/// knows whether the payload that follows needs to go through the decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum BlockTag {
+    Plain = 0,
+    Compressed = 1,
+}
+
+/// Compresses `data` and keeps whichever of the compressed or plain form is
+/// smaller, tagging the result so `decode_block` can tell them apart.
+/// Mirrors the approach Garage uses for its block store: incompressible
+/// chunks (already-compressed media, encrypted payloads) round-trip with
+/// just a one-byte tag instead of growing.
+///
+/// `min_ratio` is the minimum fractional size reduction (see
+/// `get_compression_ratio`) required before the compressed form is kept -
+/// below that, the plain bytes are stored instead.
+pub fn encode_block(data: &[u8], compression_type: CompressionType, min_ratio: f64) -> Result<Vec<u8>> {
+    let compressed = compress_data(data, compression_type)?;
+    let ratio = get_compression_ratio(data.len(), compressed.len());
+
+    let mut block = Vec::with_capacity(compressed.len().min(data.len()) + 1);
+    if ratio >= min_ratio {
+        block.push(BlockTag::Compressed as u8);
+        block.extend_from_slice(&compressed);
+    } else {
+        block.push(BlockTag::Plain as u8);
+        block.extend_from_slice(data);
+    }
+    Ok(block)
+}
+
+/// Reverses `encode_block`, reading the leading tag byte to decide whether
+/// the remaining bytes need decompressing.
+pub fn decode_block(block: &[u8], compression_type: CompressionType) -> Result<Vec<u8>> {
+    let (&tag, payload) = block.split_first().ok_or_else(|| anyhow!("Data block is empty"))?;
+
+    if tag == BlockTag::Plain as u8 {
+        Ok(payload.to_vec())
+    } else if tag == BlockTag::Compressed as u8 {
+        decompress_data(payload, compression_type)
+    } else {
+        Err(anyhow!("Unknown data block tag: {}", tag))
+    }
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const XZ_MAGIC: [u8; 6] = [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Inspects `data`'s leading bytes for a well-known container signature.
+/// Gzip and xz and zstd all have one; brotli and lz4's block format don't,
+/// so they can't be told apart from raw bytes and are never returned here -
+/// callers that need to handle those should fall back to an explicit hint
+/// or `decompress_autodetect`'s trial-decode.
+pub fn detect_compression(data: &[u8]) -> Option<CompressionType> {
+    if data.starts_with(&GZIP_MAGIC) {
+        Some(CompressionType::Gzip)
+    } else if data.starts_with(&XZ_MAGIC) {
+        Some(CompressionType::Xz)
+    } else if data.starts_with(&ZSTD_MAGIC) {
+        Some(CompressionType::Zstd)
+    } else {
+        None
+    }
+}
+
+/// Decompresses `data` without being told the codec up front: first tries
+/// `detect_compression`'s magic-byte sniff, then falls back to trial-decoding
+/// as brotli and finally lz4, the two codecs with no reliable signature of
+/// their own. Returns both the codec that worked and the decompressed bytes,
+/// for restore paths whose codec metadata was lost.
+pub fn decompress_autodetect(data: &[u8]) -> Result<(CompressionType, Vec<u8>)> {
+    if let Some(compression_type) = detect_compression(data) {
+        return Ok((compression_type, decompress_data(data, compression_type)?));
+    }
+
+    let mut decompressed = Vec::new();
+    if decompress_brotli(data, &mut decompressed).is_ok() {
+        return Ok((CompressionType::Brotli, decompressed));
+    }
+    decompressed.clear();
+    if decompress_lz4(data, &mut decompressed).is_ok() {
+        return Ok((CompressionType::Lz4, decompressed));
+    }
+
+    Err(anyhow!("Could not autodetect compression type for buffer"))
+}
+
 pub fn get_compression_ratio(original_size: usize, compressed_size: usize) -> f64 {
     if original_size == 0 {
         return 0.0;
@@ -127,30 +421,155 @@ pub fn get_compression_ratio(original_size: usize, compressed_size: usize) -> f6
     (original_size as f64 - compressed_size as f64) / original_size as f64
 }
 
-pub fn choose_best_compression(data: &[u8]) -> Result<(CompressionType, Vec<u8>)> {
-    let types = [
-        CompressionType::Zstd,
-        CompressionType::Lz4,
-        CompressionType::Brotli,
-        CompressionType::Gzip,
-    ];
+/// Governs `choose_algorithm`'s runtime pick. `forced` pins a single
+/// algorithm - skipping sampling entirely - for deployments that would
+/// rather trade ratio for predictable CPU than let the policy decide per
+/// chunk. `sample_size` caps how many leading bytes of the chunk are
+/// sampled to estimate compressibility.
+#[derive(Debug, Clone, Copy)]
+pub struct AutoCompressionPolicy {
+    pub forced: Option<CompressionType>,
+    pub sample_size: usize,
+}
+
+impl Default for AutoCompressionPolicy {
+    fn default() -> Self {
+        Self { forced: None, sample_size: 16 * 1024 }
+    }
+}
+
+/// Cheap compressibility estimate: a real (but fast, low-level) zstd pass
+/// over the sample. A sample that barely shrinks is almost certainly
+/// high-entropy data (media, encrypted payloads); one that shrinks a lot is
+/// the repetitive/textual end of the spectrum.
+fn estimate_compressibility(sample: &[u8]) -> f64 {
+    match compress_data_with_level(sample, CompressionType::Zstd, CompressionLevel::FAST) {
+        Ok(compressed) => get_compression_ratio(sample.len(), compressed.len()),
+        Err(_) => 0.0,
+    }
+}
+
+/// Picks an algorithm for `data` by sampling its first `sample_size` bytes
+/// and scoring them for compressibility: lz4 for hot/low-ratio data where a
+/// heavier codec wouldn't pay for itself, brotli for cold highly-compressible
+/// text, and zstd as the general-purpose default in between.
+pub fn choose_algorithm(data: &[u8], policy: &AutoCompressionPolicy) -> CompressionType {
+    if let Some(forced) = policy.forced {
+        return forced;
+    }
+
+    let sample_len = policy.sample_size.min(data.len());
+    let ratio = estimate_compressibility(&data[..sample_len]);
+
+    if ratio < 0.15 {
+        CompressionType::Lz4
+    } else if ratio > 0.6 {
+        CompressionType::Brotli
+    } else {
+        CompressionType::Zstd
+    }
+}
 
-    let mut best_type = CompressionType::Zstd;
-    let mut best_compressed = compress_data(data, best_type)?;
-    let mut best_ratio = get_compression_ratio(data.len(), best_compressed.len());
+/// Picks an algorithm for `data` per `policy`, compresses it, and prefixes
+/// the result with a one-byte algorithm tag so `decompress_auto` doesn't
+/// need the caller to remember which codec was chosen.
+pub fn compress_auto(data: &[u8], policy: &AutoCompressionPolicy) -> Result<Vec<u8>> {
+    let algorithm = choose_algorithm(data, policy);
+    let compressed = compress_data(data, algorithm)?;
 
-    for &compression_type in &types[1..] {
+    let mut block = Vec::with_capacity(compressed.len() + 1);
+    block.push(algorithm.tag());
+    block.extend_from_slice(&compressed);
+    Ok(block)
+}
+
+/// Reverses `compress_auto`, reading the leading tag byte to pick the
+/// decoder.
+pub fn decompress_auto(block: &[u8]) -> Result<Vec<u8>> {
+    let (&tag, payload) = block.split_first().ok_or_else(|| anyhow!("Auto-compressed block is empty"))?;
+    let algorithm = CompressionType::from_tag(tag)?;
+    decompress_data(payload, algorithm)
+}
+
+const BEST_COMPRESSION_CANDIDATES: [CompressionType; 4] = [
+    CompressionType::Zstd,
+    CompressionType::Lz4,
+    CompressionType::Brotli,
+    CompressionType::Gzip,
+];
+
+/// Governs `choose_best_compression`'s sampling pass. `sample_size` caps how
+/// many leading bytes of the input are compressed with every candidate
+/// before committing to full passes; `incompressible_threshold` is the
+/// minimum sample ratio a candidate must clear to be worth running on the
+/// full input at all. Exposed as parameters so callers with different data
+/// profiles (small metadata blocks vs. multi-GB backup payloads) can tune
+/// the speed/ratio tradeoff instead of being stuck with one hardcoded pick.
+#[derive(Debug, Clone, Copy)]
+pub struct BestCompressionPolicy {
+    pub sample_size: usize,
+    pub incompressible_threshold: f64,
+}
+
+impl Default for BestCompressionPolicy {
+    fn default() -> Self {
+        Self {
+            sample_size: 64 * 1024,
+            incompressible_threshold: 0.02,
+        }
+    }
+}
+
+/// Outcome of `choose_best_compression`. `Plain` means no candidate cleared
+/// the sampling threshold - the data is almost certainly already compressed
+/// or encrypted - and should be stored as-is, mirroring Garage's practice of
+/// keeping a block uncompressed when zstd fails to shrink it.
+pub enum CompressionChoice {
+    Plain,
+    Compressed {
+        compression_type: CompressionType,
+        data: Vec<u8>,
+    },
+}
+
+/// Picks the best-compressing codec for `data` without paying for a full
+/// pass from every candidate up front. First compresses a bounded prefix
+/// sample (`policy.sample_size` bytes) with each candidate; any whose sample
+/// ratio doesn't clear `policy.incompressible_threshold` is dropped without
+/// ever touching the rest of the input. If nothing clears the threshold,
+/// `CompressionChoice::Plain` is returned immediately instead of running
+/// four full passes that would all lose anyway. Surviving candidates are
+/// then run on the full input and the smallest result wins.
+pub fn choose_best_compression(data: &[u8], policy: &BestCompressionPolicy) -> Result<CompressionChoice> {
+    let sample = &data[..policy.sample_size.min(data.len())];
+
+    let mut competitive = Vec::new();
+    for &compression_type in &BEST_COMPRESSION_CANDIDATES {
+        let compressed_sample = compress_data(sample, compression_type)?;
+        let ratio = get_compression_ratio(sample.len(), compressed_sample.len());
+        if ratio >= policy.incompressible_threshold {
+            competitive.push(compression_type);
+        }
+    }
+
+    if competitive.is_empty() {
+        return Ok(CompressionChoice::Plain);
+    }
+
+    let mut best: Option<(CompressionType, Vec<u8>)> = None;
+    for compression_type in competitive {
         let compressed = compress_data(data, compression_type)?;
-        let ratio = get_compression_ratio(data.len(), compressed.len());
-        
-        if ratio > best_ratio {
-            best_type = compression_type;
-            best_compressed = compressed;
-            best_ratio = ratio;
+        let is_better = match &best {
+            Some((_, current_best)) => compressed.len() < current_best.len(),
+            None => true,
+        };
+        if is_better {
+            best = Some((compression_type, compressed));
         }
     }
 
-    Ok((best_type, best_compressed))
+    let (compression_type, data) = best.expect("competitive candidates is non-empty");
+    Ok(CompressionChoice::Compressed { compression_type, data })
 }
 
 #[cfg(test)]
@@ -160,8 +579,9 @@ mod tests {
     #[test]
     fn test_zstd_compression() {
         let data = b"Hello, World! This is a test string for compression.";
-        let compressed = compress_zstd(data).unwrap();
-        let decompressed = decompress_zstd(&compressed).unwrap();
+        let compressed = compress_zstd(data, 3).unwrap();
+        let mut decompressed = Vec::new();
+        decompress_zstd(&compressed, &mut decompressed).unwrap();
         assert_eq!(data, decompressed.as_slice());
     }
 
@@ -186,8 +606,191 @@ mod tests {
     #[test]
     fn test_best_compression_choice() {
         let data = b"This is a repetitive string. ".repeat(50);
-        let (best_type, compressed) = choose_best_compression(&data).unwrap();
-        let decompressed = decompress_data(&compressed, best_type).unwrap();
-        assert_eq!(data, decompressed.as_slice());
+        match choose_best_compression(&data, &BestCompressionPolicy::default()).unwrap() {
+            CompressionChoice::Compressed { compression_type, data: compressed } => {
+                let decompressed = decompress_data(&compressed, compression_type).unwrap();
+                assert_eq!(data, decompressed.as_slice());
+            }
+            CompressionChoice::Plain => panic!("repetitive data should not be treated as incompressible"),
+        }
+    }
+
+    #[test]
+    fn test_best_compression_short_circuits_incompressible_data() {
+        // Already zstd-compressed bytes are the textbook incompressible
+        // input - every candidate should lose on the sample, so this should
+        // come back Plain without a full pass from any codec.
+        let already_compressed = compress_data(&b"x".repeat(4096), CompressionType::Zstd).unwrap();
+        let choice = choose_best_compression(&already_compressed, &BestCompressionPolicy::default()).unwrap();
+        assert!(matches!(choice, CompressionChoice::Plain));
+    }
+
+    #[test]
+    fn test_best_compression_policy_sample_size_is_bounded_by_input() {
+        // A policy sample_size larger than the input should just sample the
+        // whole thing rather than panicking on an out-of-bounds slice.
+        let data = b"small".to_vec();
+        let policy = BestCompressionPolicy { sample_size: 1024, incompressible_threshold: 0.02 };
+        assert!(choose_best_compression(&data, &policy).is_ok());
+    }
+
+    #[test]
+    fn test_encode_block_keeps_compressed_form() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(100);
+        let block = encode_block(&data, CompressionType::Zstd, 0.1).unwrap();
+        assert_eq!(block[0], BlockTag::Compressed as u8);
+        assert!(block.len() < data.len());
+
+        let decoded = decode_block(&block, CompressionType::Zstd).unwrap();
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_compress_data_with_level_round_trips_every_codec() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(50);
+
+        for compression_type in [
+            CompressionType::Zstd,
+            CompressionType::Lz4,
+            CompressionType::Brotli,
+            CompressionType::Gzip,
+            CompressionType::Xz,
+        ] {
+            for level in [CompressionLevel::FAST, CompressionLevel::DEFAULT, CompressionLevel::BEST] {
+                let compressed = compress_data_with_level(&data, compression_type, level).unwrap();
+                let decompressed = decompress_data(&compressed, compression_type).unwrap();
+                assert_eq!(data.to_vec(), decompressed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compression_level_maps_onto_each_codec_native_scale() {
+        assert_eq!(CompressionLevel::FAST.zstd_level(), CompressionLevel(1).zstd_level());
+        assert!(CompressionLevel::BEST.zstd_level() > CompressionLevel::FAST.zstd_level());
+        assert!(CompressionLevel::BEST.brotli_quality() > CompressionLevel::FAST.brotli_quality());
+        assert_eq!(CompressionLevel::BEST.xz_preset(), 9);
+        assert_eq!(CompressionLevel(15).xz_preset(), 9); // clamps above 9
+    }
+
+    #[test]
+    fn test_detect_compression_via_magic_bytes() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+
+        let gzip = compress_data(&data, CompressionType::Gzip).unwrap();
+        assert!(matches!(detect_compression(&gzip), Some(CompressionType::Gzip)));
+
+        let xz = compress_data(&data, CompressionType::Xz).unwrap();
+        assert!(matches!(detect_compression(&xz), Some(CompressionType::Xz)));
+
+        let zstd = compress_data(&data, CompressionType::Zstd).unwrap();
+        assert!(matches!(detect_compression(&zstd), Some(CompressionType::Zstd)));
+
+        let brotli = compress_data(&data, CompressionType::Brotli).unwrap();
+        assert!(detect_compression(&brotli).is_none());
+    }
+
+    #[test]
+    fn test_decompress_autodetect_round_trips_every_codec() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(20);
+
+        for compression_type in [
+            CompressionType::Zstd,
+            CompressionType::Lz4,
+            CompressionType::Brotli,
+            CompressionType::Gzip,
+            CompressionType::Xz,
+        ] {
+            let compressed = compress_data(&data, compression_type).unwrap();
+            let (detected, decompressed) = decompress_autodetect(&compressed).unwrap();
+            assert_eq!(data.to_vec(), decompressed);
+            let _ = detected; // lz4/brotli detection is a trial-decode, not a magic match
+        }
+    }
+
+    #[test]
+    fn test_compress_framed_round_trip() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(200);
+        let frame = compress_framed(&data, CompressionType::Zstd).unwrap();
+        assert_eq!(frame[0], FrameTag::Zstd as u8);
+        assert!(frame.len() < data.len());
+
+        let decoded = decompress_framed(&frame).unwrap();
+        assert_eq!(data.to_vec(), decoded);
+    }
+
+    /// High-entropy bytes from the top byte of a 64-bit LCG - unlike a
+    /// repeated byte pattern, no codec can find matches in these.
+    fn pseudo_random_bytes(len: usize) -> Vec<u8> {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compress_framed_falls_back_to_plain_for_incompressible_data() {
+        let data = pseudo_random_bytes(4096);
+        let frame = compress_framed(&data, CompressionType::Zstd).unwrap();
+        assert_eq!(frame[0], FrameTag::Plain as u8);
+
+        let decoded = decompress_framed(&frame).unwrap();
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_varint_round_trip() {
+        for value in [0u64, 1, 127, 128, 300, 16384, u64::MAX] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, value);
+            let (decoded, consumed) = read_varint(&buf).unwrap();
+            assert_eq!(decoded, value);
+            assert_eq!(consumed, buf.len());
+        }
+    }
+
+    #[test]
+    fn test_choose_algorithm_picks_lz4_for_incompressible_data() {
+        let data = pseudo_random_bytes(4096);
+        let policy = AutoCompressionPolicy::default();
+        assert!(matches!(choose_algorithm(&data, &policy), CompressionType::Lz4));
+    }
+
+    #[test]
+    fn test_choose_algorithm_picks_brotli_for_highly_compressible_text() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(500);
+        let policy = AutoCompressionPolicy::default();
+        assert!(matches!(choose_algorithm(&data, &policy), CompressionType::Brotli));
+    }
+
+    #[test]
+    fn test_choose_algorithm_honors_forced_override() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(500);
+        let policy = AutoCompressionPolicy { forced: Some(CompressionType::Gzip), sample_size: 1024 };
+        assert!(matches!(choose_algorithm(&data, &policy), CompressionType::Gzip));
+    }
+
+    #[test]
+    fn test_compress_auto_round_trip_is_self_describing() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(200);
+        let block = compress_auto(&data, &AutoCompressionPolicy::default()).unwrap();
+        let decompressed = decompress_auto(&block).unwrap();
+        assert_eq!(data.to_vec(), decompressed);
+    }
+
+    #[test]
+    fn test_encode_block_falls_back_to_plain_for_incompressible_data() {
+        // Already-random bytes won't compress, so even a 1% threshold should
+        // reject the compressed form and store the plain bytes instead.
+        let data = pseudo_random_bytes(4096);
+        let block = encode_block(&data, CompressionType::Zstd, 0.01).unwrap();
+        assert_eq!(block[0], BlockTag::Plain as u8);
+        assert_eq!(&block[1..], data.as_slice());
+
+        let decoded = decode_block(&block, CompressionType::Zstd).unwrap();
+        assert_eq!(data, decoded);
     }
 }
\ No newline at end of file
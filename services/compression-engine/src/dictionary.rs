@@ -0,0 +1,106 @@
+use anyhow::{anyhow, Result};
+
+/// Below this size a dictionary's shared statistics stop paying for
+/// themselves - the same per-chunk zstd frame header the dictionary was
+/// meant to amortize away barely exists for a chunk this small anyway, so
+/// these fall back to plain dictionary-less coding.
+pub const MIN_DICTIONARY_CHUNK_SIZE: usize = 64;
+
+/// Above this size a chunk already has enough of its own content to build a
+/// good compression window from; training overhead stops being worth it, so
+/// these also skip the dictionary and compress standalone.
+pub const MAX_DICTIONARY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Trains a zstd dictionary from a set of sample chunks. Intended for the
+/// small, structurally similar files that dominate `/data/data` backups
+/// (SQLite DBs, SharedPreferences XML, JSON) - too alike for any single
+/// chunk to compress well alone, similar enough that a shared dictionary
+/// captures their common structure once instead of per file.
+pub fn train_dictionary(samples: &[Vec<u8>], max_dict_size: usize) -> Result<Vec<u8>> {
+    if samples.is_empty() {
+        return Err(anyhow!("Cannot train a dictionary from zero samples"));
+    }
+    let dict = zstd::dict::from_samples(samples, max_dict_size)
+        .map_err(|e| anyhow!("Dictionary training failed: {}", e))?;
+    Ok(dict)
+}
+
+/// Hex-encoded BLAKE3 digest of a dictionary's bytes. Stored alongside each
+/// chunk that was encoded with it, so restore can tell which dictionary to
+/// load before decoding - the same content-addressing scheme the block store
+/// uses for chunk data itself.
+pub fn dictionary_id(dictionary: &[u8]) -> String {
+    blake3::hash(dictionary).to_hex().to_string()
+}
+
+/// Compresses `data` against a trained dictionary. Callers are expected to
+/// have already checked `data.len()` against `MIN_DICTIONARY_CHUNK_SIZE`/
+/// `MAX_DICTIONARY_CHUNK_SIZE` - this function does not fall back on its own.
+pub fn compress_with_dictionary(data: &[u8], dictionary: &[u8], level: i32) -> Result<Vec<u8>> {
+    let mut encoder = zstd::bulk::Compressor::with_dictionary(level, dictionary)?;
+    let compressed = encoder.compress(data)?;
+    Ok(compressed)
+}
+
+/// Reverses `compress_with_dictionary`. `capacity_hint` should be the
+/// original chunk's decompressed size, which the chunk metadata already
+/// carries.
+pub fn decompress_with_dictionary(data: &[u8], dictionary: &[u8], capacity_hint: usize) -> Result<Vec<u8>> {
+    let mut decoder = zstd::bulk::Decompressor::with_dictionary(dictionary)?;
+    let decompressed = decoder.decompress(data, capacity_hint)?;
+    Ok(decompressed)
+}
+
+/// Whether a chunk of `size` bytes is worth encoding against a dictionary at
+/// all, per the thresholds above.
+pub fn should_use_dictionary(size: usize) -> bool {
+    (MIN_DICTIONARY_CHUNK_SIZE..=MAX_DICTIONARY_CHUNK_SIZE).contains(&size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_set() -> Vec<Vec<u8>> {
+        (0..64)
+            .map(|i| format!("{{\"user_id\": {}, \"session\": \"abc123\", \"flags\": [1,0,1]}}", i).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_train_dictionary_rejects_empty_samples() {
+        assert!(train_dictionary(&[], 1024).is_err());
+    }
+
+    #[test]
+    fn test_dictionary_round_trip() {
+        let samples = sample_set();
+        let dict = train_dictionary(&samples, 4096).unwrap();
+        assert!(!dict.is_empty());
+
+        let target = samples[0].clone();
+        let compressed = compress_with_dictionary(&target, &dict, 3).unwrap();
+        let decompressed = decompress_with_dictionary(&compressed, &dict, target.len()).unwrap();
+        assert_eq!(target, decompressed);
+    }
+
+    #[test]
+    fn test_dictionary_id_is_stable_and_content_addressed() {
+        let samples = sample_set();
+        let dict = train_dictionary(&samples, 4096).unwrap();
+        let id_a = dictionary_id(&dict);
+        let id_b = dictionary_id(&dict);
+        assert_eq!(id_a, id_b);
+
+        let other_dict = train_dictionary(&samples[..8], 1024).unwrap();
+        assert_ne!(id_a, dictionary_id(&other_dict));
+    }
+
+    #[test]
+    fn test_should_use_dictionary_thresholds() {
+        assert!(!should_use_dictionary(MIN_DICTIONARY_CHUNK_SIZE - 1));
+        assert!(should_use_dictionary(MIN_DICTIONARY_CHUNK_SIZE));
+        assert!(should_use_dictionary(MAX_DICTIONARY_CHUNK_SIZE));
+        assert!(!should_use_dictionary(MAX_DICTIONARY_CHUNK_SIZE + 1));
+    }
+}
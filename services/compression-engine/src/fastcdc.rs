@@ -0,0 +1,206 @@
+//! Content-defined chunking (FastCDC) for deduplication.
+//!
+//! Fixed-size chunking defeats dedup whenever bytes shift - an insertion in
+//! a database file moves every later chunk boundary, so none of them match
+//! the previous backup's chunks even though most of the file is unchanged.
+//! Content-defined boundaries are derived from a rolling hash of the data
+//! itself, so they survive shifts: everything after an insertion still cuts
+//! at the same relative points.
+
+/// 64-entry gear table of random-looking constants, indexed by `byte & 0x3f`.
+/// Fixed at compile time so chunk boundaries are reproducible across runs -
+/// the same bytes always cut at the same places, which is what lets
+/// identical chunks collapse to one stored copy.
+const GEAR: [u64; 64] = [
+    0x1c80317fa3b1799d, 0xbdd640fb06671ad1, 0x3eb13b9046685257, 0x23b8c1e9392456de,
+    0x1a3d1fa7bc8960a9, 0xbd9c66b3ad3c2d6d, 0x8b9d2434e465e150, 0x972a846916419f82,
+    0x0822e8f36c031199, 0x17fc695a07a0ca6e, 0x3b8faa1837f8a88b, 0x9a1de644815ef6d1,
+    0x8fadc1a606cb0fb3, 0xb74d0fb132e70629, 0xb38a088ca65ed389, 0x6b65a6a48b8148f6,
+    0x72ff5d2a386ecbe0, 0x4737819096da1dac, 0xde8a774bcf36d58b, 0xc241330b01a9e71f,
+    0x28df6ec4ce4a2bbd, 0x6c307511b2b9437a, 0x47229389571aa876, 0x371ecd7b27cd8130,
+    0xc37459eef50bea63, 0x1a2a73ed562b0f79, 0x6142ea7d17be3111, 0x5be6128e18c26797,
+    0x580d7b71d8f56413, 0x43b7a3a69a8dca03, 0x0b1f9163ce9ff57f, 0x759cde66bacfb3d0,
+    0x1ff49b7889463e85, 0xec1b8ca1f91e1d4c, 0x142c3fe860e7a113, 0x4b0dbb418d5288f1,
+    0xa0ee89aed453dd32, 0xe2acf72f9e574f7a, 0x5c941cf0dc98d2c1, 0x3139d32c93cd59bf,
+    0x11ce5dd2b45ed1f0, 0xa9488d990bbb2599, 0xc5e7ce8a3a578a8e, 0xfc377a4c4a15544d,
+    0xdaf61a26146d3f31, 0xddd1dfb23b982ef8, 0x614ff3d719db3ad0, 0x7412b29347294739,
+    0xd58842dea2bc372f, 0x29a3b2e95d65a441, 0x5af305535ec42e08, 0xab9099a435a240ae,
+    0xb3aa7efe4458a885, 0xaefcfad8efc89849, 0x12476f57a5e5a5ab, 0xa28defe39bf00273,
+    0x88bd64072bcfbe01, 0x3eabedcbbaa80dd4, 0x7656af7229d4beef, 0x451b4cf36123fdf7,
+    0xece66fa2fd5166e6, 0xb02b61c4a3d70628, 0x3838b3268e944239, 0x5304317faf42e12f,
+];
+
+/// Min/avg/max chunk size bounds, mirroring the daemon's
+/// `BackupConfig::chunking`, whose `avg_size` defaults to `chunk_size`.
+#[derive(Debug, Clone, Copy)]
+pub struct ChunkingConfig {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+impl ChunkingConfig {
+    /// Derives min/max bounds from a single average target size: min is a
+    /// quarter of the average, max is four times it - the usual FastCDC
+    /// rule of thumb.
+    pub fn new(avg_size: usize) -> Self {
+        Self {
+            min_size: (avg_size / 4).max(1),
+            avg_size,
+            max_size: avg_size * 4,
+        }
+    }
+
+    pub fn with_bounds(min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        Self { min_size, avg_size, max_size }
+    }
+}
+
+/// One content-defined chunk: its byte range within the source buffer and
+/// its blake3 hash. Identical chunks - across backups, across versions -
+/// hash identically and collapse to one stored copy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentChunk {
+    pub offset: u64,
+    pub length: u64,
+    pub hash: [u8; 32],
+}
+
+/// FastCDC chunker using normalized chunking: a stricter mask (more one
+/// bits, lower match probability) is applied below the average target size
+/// so chunks tend to grow toward it, and a looser mask (fewer one bits,
+/// higher match probability) above it so they don't run away past it. This
+/// keeps the chunk-size distribution tight around `avg_size` without giving
+/// up content-defined boundaries.
+pub struct FastCdcChunker {
+    config: ChunkingConfig,
+    mask_small: u64,
+    mask_large: u64,
+}
+
+impl FastCdcChunker {
+    pub fn new(config: ChunkingConfig) -> Self {
+        let bits = (config.avg_size.max(1) as f64).log2().round() as u32;
+        let mask_small = Self::mask_with_bits(bits.saturating_add(1));
+        let mask_large = Self::mask_with_bits(bits.saturating_sub(1));
+        Self { config, mask_small, mask_large }
+    }
+
+    fn mask_with_bits(bits: u32) -> u64 {
+        let bits = bits.clamp(1, 63);
+        (1u64 << bits) - 1
+    }
+
+    /// Splits `data` into content-defined chunks.
+    pub fn chunk(&self, data: &[u8]) -> Vec<ContentChunk> {
+        let mut chunks = Vec::new();
+        let mut start = 0usize;
+
+        while start < data.len() {
+            let end = self.next_cut_point(&data[start..]);
+            let slice = &data[start..start + end];
+            chunks.push(ContentChunk {
+                offset: start as u64,
+                length: slice.len() as u64,
+                hash: *blake3::hash(slice).as_bytes(),
+            });
+            start += end;
+        }
+
+        chunks
+    }
+
+    /// Finds the next cut point within `data`, relative to its start. Never
+    /// returns 0 (every chunk has at least one byte) and never exceeds
+    /// `max_size` or `data.len()`.
+    fn next_cut_point(&self, data: &[u8]) -> usize {
+        let max_len = data.len().min(self.config.max_size);
+        if max_len <= self.config.min_size {
+            return max_len;
+        }
+
+        let avg_offset = self.config.avg_size.min(max_len);
+        let mut hash = 0u64;
+
+        // Skip hashing until the minimum size is reached - a cut can't land
+        // before it regardless of content.
+        for i in self.config.min_size..max_len {
+            let byte = data[i];
+            hash = (hash << 1).wrapping_add(GEAR[(byte as usize) & 0x3f]);
+
+            let mask = if i < avg_offset { self.mask_small } else { self.mask_large };
+            if hash & mask == 0 {
+                return i + 1;
+            }
+        }
+
+        max_len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deterministic_data(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunks_cover_the_whole_input_contiguously() {
+        let data = deterministic_data(200_000, 1);
+        let chunker = FastCdcChunker::new(ChunkingConfig::new(16 * 1024));
+        let chunks = chunker.chunk(&data);
+
+        let mut expected_offset = 0u64;
+        for chunk in &chunks {
+            assert_eq!(chunk.offset, expected_offset);
+            assert!(chunk.length > 0);
+            expected_offset += chunk.length;
+        }
+        assert_eq!(expected_offset, data.len() as u64);
+    }
+
+    #[test]
+    fn test_respects_min_and_max_bounds() {
+        let data = deterministic_data(500_000, 2);
+        let config = ChunkingConfig::with_bounds(4096, 16 * 1024, 64 * 1024);
+        let chunker = FastCdcChunker::new(config);
+        let chunks = chunker.chunk(&data);
+
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.length as usize <= config.max_size);
+            // The last chunk may be short if the input runs out first.
+            if i + 1 < chunks.len() {
+                assert!(chunk.length as usize >= config.min_size);
+            }
+        }
+    }
+
+    #[test]
+    fn test_insertion_does_not_reshuffle_later_chunks() {
+        // The whole point of content-defined chunking: an insertion near the
+        // front should leave most later chunk hashes unchanged, unlike
+        // fixed-size chunking where every later boundary shifts.
+        let original = deterministic_data(300_000, 3);
+        let mut shifted = Vec::with_capacity(original.len() + 37);
+        shifted.extend_from_slice(&original[..5000]);
+        shifted.extend_from_slice(&deterministic_data(37, 99));
+        shifted.extend_from_slice(&original[5000..]);
+
+        let chunker = FastCdcChunker::new(ChunkingConfig::new(16 * 1024));
+        let original_hashes: std::collections::HashSet<_> =
+            chunker.chunk(&original).into_iter().map(|c| c.hash).collect();
+        let shifted_hashes: std::collections::HashSet<_> =
+            chunker.chunk(&shifted).into_iter().map(|c| c.hash).collect();
+
+        let shared = original_hashes.intersection(&shifted_hashes).count();
+        assert!(shared > 0, "expected at least some chunks to survive the insertion unchanged");
+    }
+}
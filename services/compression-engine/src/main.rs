@@ -4,10 +4,15 @@ use tokio::signal;
 use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
+mod chunked;
+mod codec;
 mod compression;
 mod config;
+mod dictionary;
+mod fastcdc;
 mod metrics;
 mod server;
+mod stream;
 
 use crate::config::Config;
 use crate::server::CompressionServer;
@@ -0,0 +1,89 @@
+use crate::compression::CompressionType;
+use anyhow::{anyhow, Result};
+use async_compression::tokio::bufread::{
+    BrotliDecoder, BrotliEncoder, GzipDecoder, GzipEncoder, XzDecoder, XzEncoder, ZstdDecoder, ZstdEncoder,
+};
+use async_compression::Level;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+
+/// Streams `src` through `compression_type`'s encoder into `dst` in bounded
+/// chunks, so compressing a multi-gigabyte filesystem snapshot never
+/// requires holding the whole thing - compressed or not - in memory at
+/// once. Modeled on `async-compression`'s `bufread` encoders. Returns the
+/// number of compressed bytes written.
+///
+/// `lz4`'s block format has no streaming frame support in this engine;
+/// callers that chose `Lz4` should use `compress_data` on bounded chunks
+/// themselves instead.
+pub async fn compress_stream<R, W>(
+    src: R,
+    mut dst: W,
+    compression_type: CompressionType,
+    level: i32,
+) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let reader = BufReader::new(src);
+    let level = Level::Precise(level);
+
+    let written = match compression_type {
+        CompressionType::Zstd => tokio::io::copy(&mut ZstdEncoder::with_quality(reader, level), &mut dst).await?,
+        CompressionType::Gzip => tokio::io::copy(&mut GzipEncoder::with_quality(reader, level), &mut dst).await?,
+        CompressionType::Brotli => tokio::io::copy(&mut BrotliEncoder::with_quality(reader, level), &mut dst).await?,
+        CompressionType::Xz => tokio::io::copy(&mut XzEncoder::with_quality(reader, level), &mut dst).await?,
+        CompressionType::Lz4 => return Err(anyhow!("lz4 has no streaming frame support; compress bounded chunks with compress_data instead")),
+    };
+
+    dst.flush().await?;
+    Ok(written)
+}
+
+/// Reverses `compress_stream`: streams `src` through `compression_type`'s
+/// decoder into `dst` in bounded chunks. Returns the number of
+/// decompressed bytes written.
+pub async fn decompress_stream<R, W>(src: R, mut dst: W, compression_type: CompressionType) -> Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let reader = BufReader::new(src);
+
+    let written = match compression_type {
+        CompressionType::Zstd => tokio::io::copy(&mut ZstdDecoder::new(reader), &mut dst).await?,
+        CompressionType::Gzip => tokio::io::copy(&mut GzipDecoder::new(reader), &mut dst).await?,
+        CompressionType::Brotli => tokio::io::copy(&mut BrotliDecoder::new(reader), &mut dst).await?,
+        CompressionType::Xz => tokio::io::copy(&mut XzDecoder::new(reader), &mut dst).await?,
+        CompressionType::Lz4 => return Err(anyhow!("lz4 has no streaming frame support; decompress bounded chunks with decompress_data instead")),
+    };
+
+    dst.flush().await?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_compress_stream_round_trip() {
+        let data = b"The quick brown fox jumps over the lazy dog. ".repeat(2000);
+
+        let mut compressed = Vec::new();
+        compress_stream(data.as_slice(), &mut compressed, CompressionType::Zstd, 3).await.unwrap();
+        assert!(compressed.len() < data.len());
+
+        let mut decompressed = Vec::new();
+        decompress_stream(compressed.as_slice(), &mut decompressed, CompressionType::Zstd).await.unwrap();
+        assert_eq!(data.to_vec(), decompressed);
+    }
+
+    #[tokio::test]
+    async fn test_compress_stream_rejects_lz4() {
+        let data = b"hello world".to_vec();
+        let mut compressed = Vec::new();
+        let result = compress_stream(data.as_slice(), &mut compressed, CompressionType::Lz4, 3).await;
+        assert!(result.is_err());
+    }
+}
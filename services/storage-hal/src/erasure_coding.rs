@@ -7,6 +7,13 @@ use blake3::hash as calculate_blake3;
 pub enum ErasureError {
     InsufficientShards,
     EncodingError(reed_solomon_erasure::Error),
+    /// The Merkle root recomputed after reconstruction didn't match the
+    /// root that was recorded at encode time - the rebuilt buffer can't be
+    /// trusted even though erasure decoding itself succeeded.
+    IntegrityMismatch,
+    /// The pre-shard compression or post-reconstruction decompression step
+    /// failed.
+    CompressionError(std::io::Error),
 }
 
 impl From<reed_solomon_erasure::Error> for ErasureError {
@@ -29,6 +36,177 @@ pub struct Shard {
     pub shard_type: ShardType,
 }
 
+// --- Merkle Tree Integrity Binding ---
+
+/// Leaf size for the Merkle tree built over the payload handed to erasure
+/// coding (the data after the fused compression step) - independent of the
+/// erasure shard size, since the tree's job is to bind the reconstructed
+/// buffer as a whole, not any one shard.
+pub const MERKLE_LEAF_SIZE: usize = 4096;
+
+/// Sibling-hash path proving a single leaf's inclusion under a root, so a
+/// chunk can be checked in O(log n) without the rest of the data.
+#[derive(Debug, Clone)]
+pub struct MerkleProof {
+    pub leaf_index: usize,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+/// Merkle tree over fixed-size leaves of the pre-erasure-coding payload: `parent =
+/// blake3(left || right)`, with the last node of an odd level duplicated
+/// rather than promoted unpaired. Ties together shards that are otherwise
+/// only checksummed individually, so a reconstructed buffer can be verified
+/// as a whole.
+pub struct MerkleTree {
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn build(data: &[u8]) -> Self {
+        let mut leaves: Vec<[u8; 32]> = data
+            .chunks(MERKLE_LEAF_SIZE)
+            .map(|chunk| *calculate_blake3(chunk).as_bytes())
+            .collect();
+        if leaves.is_empty() {
+            leaves.push(*calculate_blake3(&[]).as_bytes());
+        }
+
+        let mut levels = vec![leaves];
+        while levels.last().unwrap().len() > 1 {
+            let prev = levels.last().unwrap();
+            let mut next = Vec::with_capacity((prev.len() + 1) / 2);
+            for pair in prev.chunks(2) {
+                let left = pair[0];
+                let right = if pair.len() == 2 { pair[1] } else { pair[0] };
+                next.push(Self::hash_pair(&left, &right));
+            }
+            levels.push(next);
+        }
+
+        Self { levels }
+    }
+
+    fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(left);
+        hasher.update(right);
+        *hasher.finalize().as_bytes()
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().unwrap()[0]
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Builds the sibling-hash path for `leaf_index`, for later verification
+    /// via `verify_chunk` without rebuilding the whole tree.
+    pub fn proof(&self, leaf_index: usize) -> Option<MerkleProof> {
+        if leaf_index >= self.leaf_count() {
+            return None;
+        }
+
+        let mut siblings = Vec::with_capacity(self.levels.len().saturating_sub(1));
+        let mut index = leaf_index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling_index = if index % 2 == 0 {
+                (index + 1).min(level.len() - 1)
+            } else {
+                index - 1
+            };
+            siblings.push(level[sibling_index]);
+            index /= 2;
+        }
+
+        Some(MerkleProof { leaf_index, siblings })
+    }
+}
+
+/// Checks a single leaf against `root` using its sibling-hash path in
+/// O(log n), so the daemon can validate a partially-fetched restore without
+/// downloading everything.
+pub fn verify_chunk(leaf_data: &[u8], proof: &MerkleProof, root: &[u8; 32]) -> bool {
+    let mut hash = *calculate_blake3(leaf_data).as_bytes();
+    let mut index = proof.leaf_index;
+
+    for sibling in &proof.siblings {
+        hash = if index % 2 == 0 {
+            MerkleTree::hash_pair(&hash, sibling)
+        } else {
+            MerkleTree::hash_pair(sibling, &hash)
+        };
+        index /= 2;
+    }
+
+    &hash == root
+}
+
+// --- Fused Compression ---
+
+/// Codec chosen by `choose_compression`, recorded in `CompressionManifest` so
+/// `reconstruct_backup` can reverse it once Reed-Solomon recovery hands back
+/// the compressed buffer. Kept local to this crate rather than depending on
+/// the compression engine's own `CompressionType`, since the two services
+/// aren't linked by a shared library - just the one codec this crate needs
+/// for now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionKind {
+    None,
+    Zstd,
+}
+
+const ZSTD_LEVEL: i32 = 3;
+
+/// Recorded alongside the shards: which codec (if any) was applied before
+/// splitting into shards, the original uncompressed length (so decode knows
+/// how big a buffer to expect back), and the compressed payload's own
+/// length before it was zero-padded out to a multiple of `data_shards`.
+/// `payload_len` is what lets `reconstruct_backup` trim the padding back off
+/// before recomputing the Merkle root - without it the rebuilt buffer is
+/// longer than what `encode_backup` hashed and the root can never match.
+#[derive(Debug, Clone)]
+pub struct CompressionManifest {
+    pub kind: CompressionKind,
+    pub original_len: u64,
+    pub payload_len: u64,
+}
+
+/// Compresses `data` with zstd and keeps whichever of the compressed or
+/// original form is smaller - the same plain/compressed fallback the
+/// compression engine's `encode_block` uses, so incompressible backups
+/// (already-compressed media, encrypted payloads) don't pay for a codec
+/// round-trip that only grows them.
+fn choose_compression(data: &[u8]) -> Result<(CompressionKind, Vec<u8>), ErasureError> {
+    let compressed = zstd::encode_all(data, ZSTD_LEVEL).map_err(ErasureError::CompressionError)?;
+    if compressed.len() < data.len() {
+        Ok((CompressionKind::Zstd, compressed))
+    } else {
+        Ok((CompressionKind::None, data.to_vec()))
+    }
+}
+
+fn reverse_compression(payload: &[u8], manifest: &CompressionManifest) -> Result<Vec<u8>, ErasureError> {
+    let decompressed = match manifest.kind {
+        CompressionKind::None => payload.to_vec(),
+        CompressionKind::Zstd => zstd::decode_all(payload).map_err(ErasureError::CompressionError)?,
+    };
+
+    if decompressed.len() as u64 != manifest.original_len {
+        return Err(ErasureError::CompressionError(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "Compression manifest declared {} bytes but decompression produced {}",
+                manifest.original_len,
+                decompressed.len()
+            ),
+        )));
+    }
+
+    Ok(decompressed)
+}
+
 // --- ErasureCoder Implementation ---
 
 pub struct ErasureCoder {
@@ -47,17 +225,24 @@ impl ErasureCoder {
         })
     }
     
-    pub fn encode_backup(&self, data: &[u8]) -> Result<Vec<Shard>, ErasureError> {
-        let shard_size = (data.len() + self.data_shards - 1) / self.data_shards;
+    /// Compresses `data` (falling back to storing it as-is if compression
+    /// doesn't help), then splits the resulting payload into Reed-Solomon
+    /// shards. The chosen codec and original length are recorded in the
+    /// returned `CompressionManifest` so `reconstruct_backup` can reverse
+    /// both steps in order: erasure recovery first, decompression second.
+    pub fn encode_backup(&self, data: &[u8]) -> Result<EncodedBackup, ErasureError> {
+        let (kind, payload) = choose_compression(data)?;
+
+        let shard_size = (payload.len() + self.data_shards - 1) / self.data_shards;
         let mut shards_data: Vec<Vec<u8>> = vec![vec![0u8; shard_size]; self.data_shards + self.parity_shards];
-        
-        for (i, chunk) in data.chunks(shard_size).enumerate() {
+
+        for (i, chunk) in payload.chunks(shard_size).enumerate() {
             shards_data[i][..chunk.len()].copy_from_slice(chunk);
         }
-        
+
         self.encoder.encode(&mut shards_data)?;
-        
-        Ok(shards_data.into_iter().enumerate().map(|(index, data)| {
+
+        let shards = shards_data.into_iter().enumerate().map(|(index, data)| {
             Shard {
                 index,
                 checksum: calculate_blake3(&data),
@@ -68,31 +253,135 @@ impl ErasureCoder {
                 },
                 data,
             }
-        }).collect())
+        }).collect();
+
+        Ok(EncodedBackup {
+            shards,
+            merkle_root: MerkleTree::build(&payload).root(),
+            manifest: CompressionManifest {
+                kind,
+                original_len: data.len() as u64,
+                payload_len: payload.len() as u64,
+            },
+        })
     }
-    
-    pub fn reconstruct_backup(&self, available_shards: Vec<Option<Shard>>) -> Result<Vec<u8>, ErasureError> {
-        if available_shards.iter().filter(|s| s.is_some()).count() < self.data_shards {
-            return Err(ErasureError::InsufficientShards);
-        }
-        
+
+    /// Reconstructs the compressed payload from whatever shards are
+    /// available, then recomputes the Merkle root over the rebuilt buffer
+    /// and rejects it with `ErasureError::IntegrityMismatch` if it doesn't
+    /// match `expected_root`, and finally reverses `manifest`'s compression
+    /// to recover the original data.
+    ///
+    /// Each available shard's BLAKE3 `checksum` is checked before
+    /// reconstruction - a shard that fails the check is dropped into the
+    /// `None` slot exactly like a missing shard, so corrupted input can't
+    /// silently poison the recovered data the way it would if it were
+    /// handed straight to Reed-Solomon decoding.
+    pub fn reconstruct_backup(
+        &self,
+        available_shards: Vec<Option<Shard>>,
+        expected_root: [u8; 32],
+        manifest: &CompressionManifest,
+    ) -> Result<Vec<u8>, ErasureError> {
         let mut shards_data: Vec<Option<Vec<u8>>> = available_shards
             .into_iter()
-            .map(|shard| shard.map(|s| s.data))
+            .map(|shard| shard.filter(|s| calculate_blake3(&s.data) == s.checksum).map(|s| s.data))
             .collect();
-        
+
+        if shards_data.iter().filter(|s| s.is_some()).count() < self.data_shards {
+            return Err(ErasureError::InsufficientShards);
+        }
+
         self.encoder.reconstruct(&mut shards_data)?;
-        
-        let mut result = Vec::new();
+
+        let mut payload = Vec::new();
         for shard_opt in shards_data.iter().take(self.data_shards) {
             if let Some(data) = shard_opt {
-                result.extend_from_slice(data);
+                payload.extend_from_slice(data);
             } else {
                 // This should not happen if reconstruction was successful
                 return Err(ErasureError::InsufficientShards);
             }
         }
-        
-        Ok(result)
+
+        // Data shards are zero-padded out to a multiple of `data_shards` by
+        // `encode_backup`, so the concatenated buffer above is longer than
+        // the payload that was actually hashed. Trim it back to
+        // `payload_len` before recomputing the root, or the padding tail
+        // makes every reconstruction fail integrity checks.
+        payload.truncate(manifest.payload_len as usize);
+
+        if MerkleTree::build(&payload).root() != expected_root {
+            return Err(ErasureError::IntegrityMismatch);
+        }
+
+        reverse_compression(&payload, manifest)
+    }
+}
+
+/// Result of `encode_backup` - the erasure shards, the Merkle root binding
+/// them together, and the compression manifest needed to reverse the fused
+/// compression step, all to be carried alongside the shards and checked
+/// again on reconstruction.
+pub struct EncodedBackup {
+    pub shards: Vec<Shard>,
+    pub merkle_root: [u8; 32],
+    pub manifest: CompressionManifest,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_reconstruct_round_trip_with_unaligned_payload_length() {
+        // 10 bytes over 3 data shards doesn't divide evenly - this is the
+        // common case, not an edge case, and is what the padding bug let
+        // through every time.
+        let data = b"0123456789".to_vec();
+        let coder = ErasureCoder::new(3, 2).unwrap();
+        let encoded = coder.encode_backup(&data).unwrap();
+
+        let available: Vec<Option<Shard>> = encoded.shards.into_iter().map(Some).collect();
+        let reconstructed = coder
+            .reconstruct_backup(available, encoded.merkle_root, &encoded.manifest)
+            .unwrap();
+        assert_eq!(reconstructed, data);
+    }
+
+    #[test]
+    fn test_reconstruct_drops_corrupted_shard_and_still_recovers() {
+        let data = b"The quick brown fox jumps over the lazy dog, twice over.".to_vec();
+        let coder = ErasureCoder::new(3, 2).unwrap();
+        let encoded = coder.encode_backup(&data).unwrap();
+
+        let mut available: Vec<Option<Shard>> = encoded.shards.into_iter().map(Some).collect();
+        // Flip a byte in a data shard so its checksum no longer matches -
+        // it should be dropped into `None` instead of poisoning the result.
+        if let Some(shard) = available[0].as_mut() {
+            shard.data[0] ^= 0xff;
+        }
+
+        let reconstructed = coder
+            .reconstruct_backup(available, encoded.merkle_root, &encoded.manifest)
+            .unwrap();
+        assert_eq!(reconstructed, data);
+    }
+
+    #[test]
+    fn test_reconstruct_fails_with_insufficient_shards() {
+        let data = b"not enough shards to go around".to_vec();
+        let coder = ErasureCoder::new(3, 2).unwrap();
+        let encoded = coder.encode_backup(&data).unwrap();
+
+        let mut available: Vec<Option<Shard>> = encoded.shards.into_iter().map(Some).collect();
+        // Only 2 of the 5 total shards survive - fewer than the 3 data
+        // shards required to reconstruct anything.
+        available[0] = None;
+        available[1] = None;
+        available[3] = None;
+
+        let result = coder.reconstruct_backup(available, encoded.merkle_root, &encoded.manifest);
+        assert!(matches!(result, Err(ErasureError::InsufficientShards)));
     }
 }
\ No newline at end of file
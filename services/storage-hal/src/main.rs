@@ -1,5 +1,6 @@
 mod erasure_coding;
 
+use std::convert::TryInto;
 use tonic::{transport::Server, Request, Response, Status};
 use erasure_coding::{ErasureCoder, ErasureError};
 
@@ -29,10 +30,10 @@ impl StorageHal for StorageHalService {
         let coder = ErasureCoder::new(req.data_shards as usize, req.parity_shards as usize)
             .map_err(|e| Status::invalid_argument(format!("Failed to create encoder: {:?}", e)))?;
         
-        let encoded_shards = coder.encode_backup(&req.data)
+        let encoded = coder.encode_backup(&req.data)
             .map_err(|e| Status::internal(format!("Encoding failed: {:?}", e)))?;
 
-        let proto_shards = encoded_shards.into_iter().map(|s| Shard {
+        let proto_shards = encoded.shards.into_iter().map(|s| Shard {
             index: s.index as u32,
             data: s.data,
             checksum: s.checksum.as_bytes().to_vec(),
@@ -42,7 +43,16 @@ impl StorageHal for StorageHalService {
             },
         }).collect();
 
-        Ok(Response::new(EncodeResponse { shards: proto_shards }))
+        Ok(Response::new(EncodeResponse {
+            shards: proto_shards,
+            merkle_root: encoded.merkle_root.to_vec(),
+            compression_kind: match encoded.manifest.kind {
+                erasure_coding::CompressionKind::None => 0,
+                erasure_coding::CompressionKind::Zstd => 1,
+            },
+            original_len: encoded.manifest.original_len,
+            payload_len: encoded.manifest.payload_len,
+        }))
     }
 
     async fn reconstruct(
@@ -66,8 +76,24 @@ impl StorageHal for StorageHalService {
             })
         }).collect();
 
-        let reconstructed_data = coder.reconstruct_backup(shards_to_reconstruct)
-             .map_err(|e| Status::internal(format!("Reconstruction failed: {:?}", e)))?;
+        let expected_root: [u8; 32] = req.merkle_root.as_slice().try_into()
+            .map_err(|_| Status::invalid_argument("merkle_root must be exactly 32 bytes"))?;
+
+        let manifest = erasure_coding::CompressionManifest {
+            kind: match req.compression_kind {
+                0 => erasure_coding::CompressionKind::None,
+                _ => erasure_coding::CompressionKind::Zstd,
+            },
+            original_len: req.original_len,
+            payload_len: req.payload_len,
+        };
+
+        let reconstructed_data = coder.reconstruct_backup(shards_to_reconstruct, expected_root, &manifest)
+             .map_err(|e| match e {
+                 ErasureError::IntegrityMismatch => Status::data_loss("Reconstructed data failed Merkle root verification"),
+                 ErasureError::CompressionError(err) => Status::internal(format!("Decompression failed: {}", err)),
+                 other => Status::internal(format!("Reconstruction failed: {:?}", other)),
+             })?;
 
         Ok(Response::new(ReconstructResponse { data: reconstructed_data }))
     }